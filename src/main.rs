@@ -3,8 +3,12 @@ use glib::clone;
 use gtk::prelude::*;
 use gtk::{gdk,glib};
 use std::cell::RefCell;
+use std::path::Path;
 use std::rc::Rc;
 
+// How many completions the popover under the entry offers
+const COMPLETIONS: usize = 5;
+
 fn main() {
     let application = adw::Application::new(Some("com.gtk-rs.spelling_corrector"), Default::default());
 
@@ -54,20 +58,94 @@ fn build_ui(app: &adw::Application) {
 
 
     let go_btn = gtk::Button::with_label("Find Word");
+    let add_btn = gtk::Button::with_label("Add Word");
     text_container.append(&go_btn);
     text_container.append(&output);
+    text_container.append(&add_btn);
 
     let corrector = Rc::new(RefCell::new(spell_corrector::SpellCorrector::new()));
 
 
-    corrector.borrow_mut().use_dictionary("notsobig.txt".to_string());
+    corrector.borrow_mut().set_completion_cache(true);
+    // Parsing the text dictionary is slow, so later launches read a binary copy of it instead,
+    // unless the text has been edited since the copy was made
+    let binary_path = glib::user_cache_dir().join("spelling_corrector").join("notsobig.dict");
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let cache_fresh = match (modified(&binary_path), modified(Path::new("notsobig.txt"))) {
+        (Some(cached), Some(text)) => cached >= text,
+        (cached, _) => cached.is_some(),
+    };
+    if !cache_fresh || corrector.borrow_mut().load_dictionary(&binary_path).is_err() {
+        if let Err(error) = corrector.borrow_mut().use_dictionary("notsobig.txt".to_string()) {
+            output.set_text(&error.to_string());
+            go_btn.set_sensitive(false);
+        }
+        else if let Some(parent) = binary_path.parent() {
+            let _ = std::fs::create_dir_all(parent).map(|_| corrector.borrow().save_dictionary(&binary_path));
+        }
+    }
+    let personal_path = glib::user_data_dir().join("spelling_corrector").join("personal.txt");
+    if let Err(error) = corrector.borrow_mut().use_personal_dictionary(&personal_path) {
+        output.set_text(&error.to_string());
+    }
+
+    let completion_list = gtk::ListBox::new();
+    let completion_popover = gtk::Popover::builder()
+        .child(&completion_list)
+        .autohide(false)
+        .has_arrow(false)
+        .position(gtk::PositionType::Bottom)
+        .build();
+    completion_popover.set_parent(&text_to_be_corrected);
+    text_to_be_corrected.connect_destroy(clone!(@weak completion_popover => move |_entry| {
+        completion_popover.unparent();
+    }));
+    let changed_handler = text_to_be_corrected.connect_changed(clone!(@weak completion_popover,@weak completion_list,@strong corrector => move |entry| {
+        while let Some(row) = completion_list.first_child() {
+            completion_list.remove(&row);
+        }
+
+        let completions = corrector.borrow().complete(entry.text().as_str().to_string(), COMPLETIONS).unwrap_or_default();
+        if completions.is_empty() {
+            completion_popover.popdown();
+            return;
+        }
+        for word in completions {
+            let label = gtk::Label::builder()
+                .label(&word)
+                .halign(gtk::Align::Start)
+                .build();
+            completion_list.append(&label);
+        }
+        completion_popover.popup();
+    }));
+    completion_list.connect_row_activated(clone!(@weak text_to_be_corrected,@weak completion_popover => move |_list, row| {
+        if let Some(label) = row.child().and_then(|child| child.downcast::<gtk::Label>().ok()) {
+            // Filling in the chosen word shouldn't offer it straight back as a completion
+            text_to_be_corrected.block_signal(&changed_handler);
+            text_to_be_corrected.set_text(&label.label());
+            text_to_be_corrected.unblock_signal(&changed_handler);
+            text_to_be_corrected.set_position(-1);
+        }
+        completion_popover.popdown();
+    }));
+
+    add_btn.connect_clicked(clone!(@weak output,@weak text_to_be_corrected,@strong corrector => move |_btn| {
+        let word_to_add = text_to_be_corrected.text();
+
+        match corrector.borrow_mut().add_word(word_to_add.as_str().to_string()) {
+            Err(error) => output.set_text(&error.to_string()),
+            Ok(()) => output.set_text(&format!("Added \"{}\"", word_to_add))
+        }
+    }));
     go_btn.connect_clicked(clone!(@weak output,@weak text_to_be_corrected => move |_btn| {
         let word_to_use = text_to_be_corrected.text();
         
         let suggestion = corrector.borrow_mut().suggest_similar_word(word_to_use.as_str().to_string());
 
         match suggestion {
-            Err(_) => output.set_text("No similar word found"),
+            Err(spell_corrector::SpellError::NoCandidate(_)) => output.set_text("No similar word found"),
+            Err(error) => output.set_text(&error.to_string()),
             Ok(word) => output.set_text(&word)
         }
     }));
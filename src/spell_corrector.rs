@@ -1,226 +1,961 @@
+mod binary;
+mod bktree;
+mod casing;
+mod channel;
+mod context;
+mod dawg;
+mod distance;
+mod error;
+mod frozen;
+mod keyboard;
+mod mapped;
+mod pattern;
+mod personal;
+mod phonetic;
+mod suffix;
+mod symspell;
+mod tokenizer;
 mod trie;
 
+pub use dawg::CompressionStats;
+pub use error::SpellError;
+pub use keyboard::KeyboardLayout;
+pub use phonetic::PhoneticEncoder;
+pub use tokenizer::{tokenize, Token};
+
+use trie::TrieCursor;
 
 use std::fs;
+use std::path::Path;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const DEFAULT_MAX_DISTANCE: u32 = 2;
+// How many suggestions each word of a sentence is weighed against
+const SENTENCE_CANDIDATES: usize = 10;
+// Without an error model, each edit makes a word ten times less likely to be what was meant
+const EDIT_PROBABILITY: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    TrieWalk,
+    EditGeneration,
+    SymSpell,
+    BkTree,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    word: String,
+    distance: u32,
+    cost: f64,
+    frequency: u32,
+    probability: f64,
+    score: f64,
+}
+
+impl Suggestion {
+    pub fn get_word(&self) -> &str {
+        &self.word
+    }
+    pub fn get_distance(&self) -> u32 {
+        self.distance
+    }
+    pub fn get_cost(&self) -> f64 {
+        self.cost
+    }
+    pub fn get_freq(&self) -> u32 {
+        self.frequency
+    }
+    pub fn get_probability(&self) -> f64 {
+        self.probability
+    }
+    pub fn get_score(&self) -> f64 {
+        self.score
+    }
+}
 
-use std::collections::HashSet;
+#[derive(Debug, Clone, PartialEq)]
+pub struct Misspelling {
+    token: Token,
+    suggestions: Vec<Suggestion>,
+}
+
+impl Misspelling {
+    pub fn get_word(&self) -> &str {
+        self.token.get_word()
+    }
+    pub fn get_start(&self) -> usize {
+        self.token.get_start()
+    }
+    pub fn get_end(&self) -> usize {
+        self.token.get_end()
+    }
+    pub fn get_suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+}
 
 #[derive(Clone)]
 pub struct SpellCorrector {
+    // Empty when the dictionary is frozen
     dictionary: trie::Trie,
+    frozen_dictionary: Option<frozen::FrozenDictionary>,
+    alphabet: Vec<char>,
+    max_distance: u32,
+    backend: Backend,
+    delete_index: Option<symspell::DeleteIndex>,
+    bk_tree: Option<bktree::BkTree>,
+    keyboard_layout: Option<KeyboardLayout>,
+    phonetic_index: Option<phonetic::PhoneticIndex>,
+    suffix_index: Option<suffix::SuffixIndex>,
+    error_model: Option<channel::ErrorModel>,
+    context_model: Option<context::ContextModel>,
+    total_freq: u64,
+    // Words the dictionary only ever capitalizes one way, like "NASA" or "iPhone", by lowercase form
+    capitalization: HashMap<String, String>,
+    personal: personal::PersonalDictionary,
+    // The user's words the dictionary lacks, kept apart so saving the dictionary leaves them out
+    personal_words: trie::Trie,
+    personal_capitalization: HashMap<String, String>,
+    anagram_fallback: bool,
 }
 
 impl SpellCorrector {
 
     pub fn new() -> Self {
-        Self {dictionary: trie::Trie::new()}
+        Self {dictionary: trie::Trie::new(), frozen_dictionary: None, alphabet: Vec::new(), max_distance: DEFAULT_MAX_DISTANCE, backend: Backend::TrieWalk, delete_index: None, bk_tree: None, keyboard_layout: None, phonetic_index: None, suffix_index: None, error_model: None, context_model: None, total_freq: 0, capitalization: HashMap::new(), personal: personal::PersonalDictionary::new(), personal_words: trie::Trie::new(), personal_capitalization: HashMap::new(), anagram_fallback: false}
+    }
+
+    pub fn set_max_distance(&mut self, max_distance: u32) {
+        self.max_distance = max_distance;
+        self.prepare_backend();
+    }
+    pub fn get_max_distance(&self) -> u32 {
+        self.max_distance
     }
-    pub fn use_dictionary(&mut self, dictionary_file_name: String) {
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+        self.prepare_backend();
+    }
+    pub fn get_backend(&self) -> Backend {
+        self.backend
+    }
+    pub fn set_keyboard_layout(&mut self, keyboard_layout: Option<KeyboardLayout>) {
+        self.keyboard_layout = keyboard_layout;
+    }
+    pub fn get_keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard_layout
+    }
+    // Offers anagrams of a badly scrambled word when nothing is within max_distance, before
+    // trying words that sound like it
+    pub fn set_anagram_fallback(&mut self, enabled: bool) {
+        self.anagram_fallback = enabled;
+    }
+    pub fn get_anagram_fallback(&self) -> bool {
+        self.anagram_fallback
+    }
+    // Words that sound like the input are only offered when nothing is within max_distance
+    pub fn set_phonetic_encoder(&mut self, phonetic_encoder: Option<PhoneticEncoder>) {
+        self.phonetic_index = phonetic_encoder.map(|encoder| {
+            let mut index = phonetic::PhoneticIndex::new(encoder);
+            for (word, _) in self.dictionary_words() {
+                index.add(&word);
+            }
+            index
+        });
+    }
+    pub fn get_phonetic_encoder(&self) -> Option<PhoneticEncoder> {
+        self.phonetic_index.as_ref().map(|index| index.get_encoder())
+    }
+    // Trains an error model from misspelling and correction pairs and ranks by P(word)·P(typo|word)
+    pub fn use_error_model(&mut self, error_model_file_name: String) -> Result<(), SpellError> {
+        self.error_model = Some(channel::ErrorModel::from_file(&error_model_file_name)?);
+        Ok(())
+    }
+    pub fn clear_error_model(&mut self) {
+        self.error_model = None;
+    }
+    pub fn has_error_model(&self) -> bool {
+        self.error_model.is_some()
+    }
+    // Counts word bigrams and trigrams in a corpus so correct_sentence can weigh the neighbours
+    pub fn use_context_model(&mut self, corpus_file_name: String) -> Result<(), SpellError> {
+        self.context_model = Some(context::ContextModel::from_file(&corpus_file_name)?);
+        Ok(())
+    }
+    pub fn clear_context_model(&mut self) {
+        self.context_model = None;
+    }
+    pub fn has_context_model(&self) -> bool {
+        self.context_model.is_some()
+    }
+    pub fn use_dictionary(&mut self, dictionary_file_name: String) -> Result<(), SpellError> {
+        let file = fs::read_to_string(dictionary_file_name)?;
+        let completion_cache = self.dictionary.has_max_freq_cache();
         self.dictionary = trie::Trie::new();
-        let file = fs::read_to_string(dictionary_file_name);
+        self.frozen_dictionary = None;
+        self.total_freq = 0;
+        let mut alphabet = BTreeSet::new();
         
-        for line in file.expect("Unable to find dictionary").lines() {
-            
-            for word in line.split(' ') {
-                let mut lower_word = word.to_lowercase();
-                lower_word = lower_word.trim_end().to_string();
-                self.dictionary.add(&lower_word.to_string());
+        // A word is marked as always capitalized when the dictionary never writes it in lowercase.
+        // Capitals opening a sentence count neither way
+        let mut capitalization: HashMap<String, String> = HashMap::new();
+        let mut written_lower: HashSet<String> = HashSet::new();
+
+        for token in tokenizer::split_entries(&file) {
+            let lower_word = token.get_word().to_lowercase();
+            if token.get_word() == lower_word {
+                capitalization.remove(&lower_word);
+                written_lower.insert(lower_word.clone());
             }
-            
+            else if !written_lower.contains(&lower_word) && !casing::follows_sentence_end(&file, token.get_start()) {
+                capitalization.entry(lower_word.clone()).or_insert_with(|| token.get_word().to_string());
+            }
+            alphabet.extend(lower_word.chars());
+            self.total_freq += 1;
+            self.dictionary.add(&lower_word);
         }
+        self.dictionary.set_max_freq_cache(completion_cache);
+        self.capitalization = capitalization;
+        self.finish_dictionary(alphabet);
+
+        Ok(())
     }
 
-    pub fn suggest_similar_word(&mut self, input_word: String) -> Result<String, String> {
-        let lower_word = input_word.to_lowercase();
+    // Writes the dictionary in the binary format, each word with any capitals it insists on, so
+    // load_dictionary can skip tokenizing the text again
+    pub fn save_dictionary(&self, file_path: &Path) -> Result<(), SpellError> {
+        let mut words: Vec<(String, u32)> = self.base_words().map(|(word, freq)| match self.capitalization.get(&word) {
+            Some(capitalized) => (capitalized.clone(), freq),
+            None => (word, freq),
+        }).collect();
+        // Capitals move a word out of the trie's order, and encode relies on it
+        words.sort();
+        fs::write(file_path, binary::encode(words)).map_err(SpellError::Write)?;
+        Ok(())
+    }
 
-        match self.dictionary.find(&lower_word) {
-            None => {},
-            Some(_v) => return Ok(lower_word)
-        }
-
-        let mut edit_dist1 : HashSet<Box<String>> = HashSet::new();
-        self.gen_edit_dist1(&mut edit_dist1, lower_word);
-        //println!("{:?}", self.edit_dist1);
-        //println!("edit dist 1 size: {}",edit_dist1.len());
-        //println!("Finding Matches in Edit Distance 1");
-        let mut matches = Vec::new();
-        for word in edit_dist1.iter() {
-            match self.dictionary.find(&word) {
-                None => {},
-                Some(_v) => matches.push(word)
+    pub fn load_dictionary(&mut self, file_path: &Path) -> Result<(), SpellError> {
+        let words = binary::decode(&fs::read(file_path)?)?;
+        let completion_cache = self.dictionary.has_max_freq_cache();
+        self.dictionary = trie::Trie::new();
+        self.frozen_dictionary = None;
+        self.capitalization = HashMap::new();
+        self.total_freq = 0;
+        let mut alphabet = BTreeSet::new();
+
+        for (word, freq) in words {
+            let lower_word = word.to_lowercase();
+            if word != lower_word {
+                self.capitalization.insert(lower_word.clone(), word);
             }
+            alphabet.extend(lower_word.chars());
+            self.total_freq += freq as u64;
+            self.dictionary.set_freq(&lower_word, freq);
         }
+        self.dictionary.set_max_freq_cache(completion_cache);
+        self.finish_dictionary(alphabet);
 
-        let mut output : (Option<String>, u32) = (None, 0);
-        //println!("finding Highest Freq in matches");
-        for matched_word in matches.iter() {
-            let pair;
-            match self.dictionary.find(&matched_word) {
-                None => continue,
-                Some(node) => pair = (Some(matched_word.to_string()), node.get_freq())
+        Ok(())
+    }
+
+    // Queries the dictionary in place from a file written by save_mapped_dictionary instead of
+    // building it in memory. Words added afterwards go into a small trie on top of it
+    pub fn use_mapped_dictionary(&mut self, file_path: &Path) -> Result<(), SpellError> {
+        let mapped = mapped::MappedTrie::open(file_path)?;
+        let completion_cache = self.dictionary.has_max_freq_cache();
+        self.dictionary = trie::Trie::new();
+        self.dictionary.set_max_freq_cache(completion_cache);
+        self.capitalization = mapped.get_forms()?.into_iter().map(|word| (word.to_lowercase(), word)).collect();
+        self.total_freq = mapped.get_total_freq();
+        let alphabet = mapped.get_alphabet();
+        self.frozen_dictionary = Some(frozen::FrozenDictionary::Mapped(mapped));
+        self.finish_dictionary(alphabet);
+
+        Ok(())
+    }
+
+    // Writes a new file and moves it into place, so processes that have the old one mapped
+    // keep reading it undisturbed
+    pub fn save_mapped_dictionary(&self, file_path: &Path) -> Result<(), SpellError> {
+        let forms = self.capitalization.values().map(|word| word.as_str());
+        let bytes = match &self.frozen_dictionary {
+            Some(_) => mapped::MappedTrie::encode(&self.base_words().collect(), forms),
+            None => mapped::MappedTrie::encode(&self.dictionary, forms),
+        };
+        let temp_path = file_path.with_extension("tmp");
+        fs::write(&temp_path, bytes).map_err(SpellError::Write)?;
+        fs::rename(&temp_path, file_path).map_err(SpellError::Write)?;
+        Ok(())
+    }
+
+    // Shares the common endings of the dictionary's words to save memory. Lookup and suggestion
+    // work the same, and words added afterwards go into a small trie on top of it
+    pub fn minimize_dictionary(&mut self) -> CompressionStats {
+        let dawg = match &self.frozen_dictionary {
+            Some(frozen::FrozenDictionary::Minimized(dawg)) => return dawg.get_stats(),
+            Some(_) => dawg::Dawg::from_trie(&self.base_words().collect()),
+            None => dawg::Dawg::from_trie(&self.dictionary),
+        };
+        let stats = dawg.get_stats();
+
+        let completion_cache = self.dictionary.has_max_freq_cache();
+        self.dictionary = trie::Trie::new();
+        self.dictionary.set_max_freq_cache(completion_cache);
+        self.frozen_dictionary = Some(frozen::FrozenDictionary::Minimized(dawg));
+        stats
+    }
+
+    // Identifies the dictionary's words and frequencies however it was loaded or stored, the same
+    // across runs, so files compiled from it can be cached under it
+    pub fn get_dictionary_digest(&self) -> u64 {
+        match &self.frozen_dictionary {
+            Some(_) => {
+                let mut words: Vec<(String, u32)> = self.base_words().collect();
+                words.sort();
+                binary::digest(words)
             }
+            None => self.dictionary.digest(),
+        }
+    }
+
+    // Rebuilds everything derived from a freshly loaded dictionary
+    fn finish_dictionary(&mut self, alphabet: BTreeSet<char>) {
+        // Substitutions and insertions draw from every letter the dictionary uses
+        self.alphabet = alphabet.into_iter().collect();
+        self.rebuild_indexes();
+    }
+
+    fn rebuild_indexes(&mut self) {
+        self.delete_index = None;
+        self.bk_tree = None;
+        self.merge_personal_words();
+        self.prepare_backend();
+        let phonetic_encoder = self.get_phonetic_encoder();
+        self.set_phonetic_encoder(phonetic_encoder);
+        let suffix_index = self.has_suffix_index();
+        self.set_suffix_index(suffix_index);
+    }
+
+    // Caches the highest frequency under every node of the dictionary, so completion stays fast
+    // on large dictionaries. Kept across use_dictionary
+    pub fn set_completion_cache(&mut self, enabled: bool) {
+        self.dictionary.set_max_freq_cache(enabled);
+        self.personal_words.set_max_freq_cache(enabled);
+    }
+    pub fn has_completion_cache(&self) -> bool {
+        self.dictionary.has_max_freq_cache()
+    }
+
+    // Up to k dictionary words starting with the prefix, most frequent first, capitalized like
+    // the prefix
+    pub fn complete(&self, prefix: String, k: usize) -> Result<Vec<String>, SpellError> {
+        check_word(&prefix)?;
+        let lower_prefix = prefix.to_lowercase();
+
+        // Ask for enough to make up for any removed words among the most frequent
+        let wanted = k + self.personal.get_removed_count();
+        let mut completions = self.dictionary.complete(&lower_prefix, wanted);
+        if let Some(frozen) = &self.frozen_dictionary {
+            completions.extend(frozen.complete(&lower_prefix, wanted));
+        }
+        completions.extend(self.personal_words.complete(&lower_prefix, wanted));
+        completions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(completions.into_iter()
+            .filter(|(word, _)| !self.personal.is_removed(word))
+            .take(k)
+            .map(|(word, _)| self.restore_case(&prefix, &word))
+            .collect())
+    }
 
-            if output.1 < pair.1 {
-                output = pair;
+    // Dictionary words matching a pattern like "c?t", "*ing" or "[bc]a[^r]", in alphabetical
+    // order with their frequencies, capitalized like the pattern
+    pub fn find_pattern(&self, pattern: String) -> Result<Vec<(String, u32)>, SpellError> {
+        let parsed = pattern::Pattern::parse(&pattern)?;
+        let mut matches = TrieCursor::find_pattern(self.dictionary.get_root(), &parsed);
+        if let Some(frozen) = &self.frozen_dictionary {
+            matches.extend(frozen.find_pattern(&parsed));
+        }
+        matches.extend(TrieCursor::find_pattern(self.personal_words.get_root(), &parsed));
+        matches.sort();
+        Ok(matches.into_iter()
+            .filter(|(word, _)| !self.personal.is_removed(word))
+            .map(|(word, freq)| (self.restore_case(&pattern, &word), freq))
+            .collect())
+    }
+
+    // Dictionary words using exactly the given letters, most frequent first
+    pub fn anagrams(&self, letters: String) -> Result<Vec<(String, u32)>, SpellError> {
+        self.find_anagrams(letters, false)
+    }
+
+    // Dictionary words using some of the given letters, most frequent first
+    pub fn sub_anagrams(&self, letters: String) -> Result<Vec<(String, u32)>, SpellError> {
+        self.find_anagrams(letters, true)
+    }
+
+    fn find_anagrams(&self, letters: String, partial: bool) -> Result<Vec<(String, u32)>, SpellError> {
+        check_word(&letters)?;
+        let lower_letters = letters.to_lowercase();
+        let mut anagrams = TrieCursor::anagrams(self.dictionary.get_root(), &lower_letters, partial);
+        if let Some(frozen) = &self.frozen_dictionary {
+            anagrams.extend(frozen.anagrams(&lower_letters, partial));
+        }
+        anagrams.extend(TrieCursor::anagrams(self.personal_words.get_root(), &lower_letters, partial));
+        anagrams.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(anagrams.into_iter()
+            .filter(|(word, _)| !self.personal.is_removed(word))
+            .map(|(word, freq)| (self.restore_case(&letters, &word), freq))
+            .collect())
+    }
+
+    // Keeps every word spelled backwards too, so ends_with and longest_common_suffix don't scan
+    // the whole dictionary. Kept across use_dictionary
+    pub fn set_suffix_index(&mut self, enabled: bool) {
+        self.suffix_index = match enabled {
+            true => {
+                let mut index = suffix::SuffixIndex::new();
+                for (word, freq) in self.dictionary_words() {
+                    index.add(&word, freq);
+                }
+                Some(index)
             }
-            else if output.1 == pair.1 {
-                if output.0 > pair.0 {
-                    output = pair;
+            false => None,
+        };
+    }
+    pub fn has_suffix_index(&self) -> bool {
+        self.suffix_index.is_some()
+    }
+
+    // Dictionary words ending with the suffix, like "tion", most frequent first, capitalized like
+    // the suffix
+    pub fn ends_with(&self, suffix: String) -> Result<Vec<(String, u32)>, SpellError> {
+        check_word(&suffix)?;
+        let lower_suffix = suffix.to_lowercase();
+        let mut words = match &self.suffix_index {
+            Some(index) => index.ends_with(&lower_suffix),
+            None => self.dictionary_words().filter(|(word, _)| word.ends_with(&lower_suffix)).collect(),
+        };
+        words.retain(|(word, _)| !self.personal.is_removed(word));
+        words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(words.into_iter().map(|(word, freq)| (self.restore_case(&suffix, &word), freq)).collect())
+    }
+
+    // The longest ending the word shares with other dictionary words, in lowercase, and those
+    // words, most frequent first. Rhymes, roughly
+    pub fn longest_common_suffix(&self, word: String) -> Result<(String, Vec<(String, u32)>), SpellError> {
+        check_word(&word)?;
+        let lower_word = word.to_lowercase();
+        let (suffix, mut words) = match &self.suffix_index {
+            Some(index) => index.longest_common_suffix(&lower_word, |other| !self.personal.is_removed(other)),
+            None => {
+                let mut longest = 0;
+                let mut words = Vec::new();
+                for (other, freq) in self.dictionary_words() {
+                    if other == lower_word || self.personal.is_removed(&other) {
+                        continue;
+                    }
+                    let shared = lower_word.chars().rev().zip(other.chars().rev()).take_while(|(a, b)| a == b).count();
+                    if shared > longest {
+                        longest = shared;
+                        words.clear();
+                    }
+                    if shared == longest && shared > 0 {
+                        words.push((other, freq));
+                    }
                 }
+                (lower_word.chars().skip(lower_word.chars().count() - longest).collect(), words)
             }
+        };
+        words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok((suffix, words.into_iter().map(|(other, freq)| (self.restore_case(&word, &other), freq)).collect()))
+    }
 
-            
+    // Loads the user's words from a file, which is rewritten whenever one is added or removed
+    pub fn use_personal_dictionary(&mut self, file_path: &Path) -> Result<(), SpellError> {
+        self.personal = personal::PersonalDictionary::load(file_path)?;
+        match self.personal_words.get_word_count() {
+            0 => self.merge_personal_words(),
+            // The indexes still hold the words of the previous personal dictionary
+            _ => self.rebuild_indexes(),
         }
-        //println!("{:?}", matches); 
-        if output.0.is_some() {
-            return Ok(output.0.unwrap().to_string());
+        Ok(())
+    }
+
+    pub fn add_word(&mut self, word: String) -> Result<(), SpellError> {
+        check_word(&word)?;
+        self.personal.add(&word)?;
+        self.insert_word(&word);
+        Ok(())
+    }
+
+    // Hides the word from lookup and suggestion, even when the main dictionary has it
+    pub fn remove_word(&mut self, word: String) -> Result<(), SpellError> {
+        check_word(&word)?;
+        self.personal.remove(&word)
+    }
+
+    // Accepts the word as it is until the corrector is dropped, without saving it
+    pub fn ignore_word(&mut self, word: String) -> Result<(), SpellError> {
+        check_word(&word)?;
+        self.personal.ignore(&word);
+        Ok(())
+    }
+
+    pub fn is_known_word(&self, word: String) -> bool {
+        let lower_word = word.to_lowercase();
+        if self.personal.is_ignored(&lower_word) {
+            return true;
         }
+        !self.personal.is_removed(&lower_word) && self.get_word_freq(&lower_word).is_some()
+    }
 
-        let mut matches2 = Vec::new();
-        
-        let mut edit_dist2 : HashSet<Box<String>> = HashSet::new();
-        self.gen_edit_dist2(&mut edit_dist2,&edit_dist1);
-        //println!("edit dist 2 size: {}",edit_dist2.len());
-        //println!("Finding Matches in Edit Distance 2");
-        for word in edit_dist2.iter() {
-            //println!("{}",word);
-            match self.dictionary.find(&word) {
-                None => {},
-                Some(_v) => matches2.push(word)
-            }
+    fn merge_personal_words(&mut self) {
+        self.personal_words = trie::Trie::new();
+        self.personal_words.set_max_freq_cache(self.dictionary.has_max_freq_cache());
+        self.personal_capitalization = HashMap::new();
+        let words: Vec<String> = self.personal.get_added_words().into_iter().map(|word| word.to_string()).collect();
+        for word in words {
+            self.insert_word(&word);
         }
-        
-        //println!("{:?}", self.edit_dist2);
-        //println!("finding Highest Freq in matches");
-        
-        for matched_word in matches2.iter() {
-            let pair;
-            match self.dictionary.find(&matched_word) {
-                None => continue,
-                Some(node) => pair = (Some(matched_word.to_string()), node.get_freq())
-            }
+    }
 
-            if output.1 < pair.1 {
-                output = pair;
+    // Adds a word once, along with any index already built, so the user's words count as often
+    // as the rarest dictionary word
+    fn insert_word(&mut self, word: &str) {
+        let lower_word = word.to_lowercase();
+        if word != lower_word {
+            self.personal_capitalization.insert(lower_word.clone(), word.to_string());
+        }
+        if self.get_word_freq(&lower_word).is_some() {
+            return;
+        }
+
+        self.personal_words.add(&lower_word);
+        for letter in lower_word.chars() {
+            if let Err(index) = self.alphabet.binary_search(&letter) {
+                self.alphabet.insert(index, letter);
             }
-            else if output.1 == pair.1 {
-                if output.0 > pair.0 {
-                    output = pair;
+        }
+        if let Some(index) = &mut self.delete_index {
+            index.add(&lower_word);
+        }
+        if let Some(tree) = &mut self.bk_tree {
+            tree.add(&lower_word);
+        }
+        if let Some(index) = &mut self.phonetic_index {
+            index.add(&lower_word);
+        }
+        if let Some(index) = &mut self.suffix_index {
+            index.add(&lower_word, 1);
+        }
+    }
+
+    fn get_word_freq(&self, lower_word: &String) -> Option<u32> {
+        if let Some(freq) = self.frozen_dictionary.as_ref().and_then(|frozen| frozen.get_freq(lower_word)) {
+            return Some(freq);
+        }
+        self.dictionary.find(lower_word).or_else(|| self.personal_words.find(lower_word)).map(|node| node.get_freq())
+    }
+
+    // The capitals a word insists on, the user's own spelling first
+    fn get_capitalization(&self, lower_word: &str) -> Option<&String> {
+        self.personal_capitalization.get(lower_word).or_else(|| self.capitalization.get(lower_word))
+    }
+
+    // The words of the dictionary alone, without the user's
+    fn base_words(&self) -> Box<dyn Iterator<Item = (String, u32)> + '_> {
+        match &self.frozen_dictionary {
+            Some(frozen) => Box::new(frozen.iter()),
+            None => Box::new(self.dictionary.iter()),
+        }
+    }
+
+    fn dictionary_words(&self) -> Box<dyn Iterator<Item = (String, u32)> + '_> {
+        Box::new(self.base_words().chain(self.personal_words.iter()))
+    }
+
+    // The user's words never repeat a dictionary word, so the searches don't overlap
+    fn fuzzy_find(&self, lower_word: &String) -> Vec<(String, u32)> {
+        let mut candidates = self.dictionary.fuzzy_find(lower_word, self.max_distance);
+        if let Some(frozen) = &self.frozen_dictionary {
+            candidates.extend(frozen.fuzzy_find(lower_word, self.max_distance));
+        }
+        candidates.extend(self.personal_words.fuzzy_find(lower_word, self.max_distance));
+        candidates
+    }
+
+    // Builds whatever index the selected backend needs for the current dictionary and distance
+    fn prepare_backend(&mut self) {
+        if self.backend == Backend::SymSpell {
+            let up_to_date = match &self.delete_index {
+                Some(index) => index.get_max_distance() >= self.max_distance,
+                None => false,
+            };
+            if !up_to_date {
+                let mut index = symspell::DeleteIndex::new(self.max_distance);
+                for (word, _) in self.dictionary_words() {
+                    index.add(&word);
                 }
+                self.delete_index = Some(index);
             }
-            
         }
-       
-        //println!("{:?}", matches); 
-        if output.0.is_some() {
-            return Ok(output.0.unwrap().to_string());
+        if self.backend == Backend::BkTree && self.bk_tree.is_none() {
+            let mut tree = bktree::BkTree::new();
+            for (word, _) in self.dictionary_words() {
+                tree.add(&word);
+            }
+            self.bk_tree = Some(tree);
         }
-        
-        Err("Unable to find word \"".to_string() + &input_word + &"\"".to_string())
     }
 
-    fn delete_char(&mut self,words: &mut HashSet<Box<String>> , word: & String) {
-        //println!("length of word {}",word.chars().count());
-        for i in 0..(word.chars().count()) {
-            let mut new_word = Box::new(word.clone());
-            new_word.drain(i..i+1);
-            
-            words.insert(new_word);
+    pub fn suggest_similar_word(&mut self, input_word: String) -> Result<String, SpellError> {
+        match self.suggest(input_word.clone(), 1)?.into_iter().next() {
+            Some(suggestion) => Ok(suggestion.word),
+            None => Err(SpellError::NoCandidate(input_word))
         }
     }
 
-    fn transpose_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
-        for i in 0..(word.chars().count()) {
-            let char1 = word.chars().nth(i).unwrap();
-            for j in 1..(word.chars().count()) {
-                let char2 = word.chars().nth(j).unwrap();
-                if char1 == char2 {
-                    continue;
-                }
-                //println!("{} {}",char1.to_string(), char2.to_string());
-                let mut new_word = Box::new(word.clone());
+    pub fn suggest(&mut self, input_word: String, n: usize) -> Result<Vec<Suggestion>, SpellError> {
+        check_word(&input_word)?;
+        let lower_word = input_word.to_lowercase();
 
-                new_word.replace_range(i..i+1, &char2.to_string());
-                new_word.replace_range(j..j+1, &char1.to_string());
-                //println!("\t{} {}",word,new_word);
-                words.insert(new_word);
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        // The user asked for this spelling to be left alone
+        if self.personal.is_ignored(&lower_word) {
+            let frequency = self.get_word_freq(&lower_word).unwrap_or(0);
+            return Ok(vec![Suggestion {word: input_word, distance: 0, cost: 0.0, frequency, probability: 0.0, score: 1.0}]);
+        }
+
+        let mut candidates = match self.backend {
+            Backend::TrieWalk => self.fuzzy_find(&lower_word),
+            Backend::EditGeneration => self.edit_candidates(lower_word.clone(), n),
+            Backend::SymSpell => match &self.delete_index {
+                Some(index) => index.lookup(&lower_word, self.max_distance),
+                None => Vec::new(),
+            },
+            Backend::BkTree => match &self.bk_tree {
+                Some(tree) => tree.find_within(&lower_word, self.max_distance),
+                None => Vec::new(),
+            },
+        };
+        candidates.retain(|(word, _)| !self.personal.is_removed(word));
+
+        // Scrambling more than a couple of letters puts a word beyond max_distance, but it still
+        // has the same letters
+        if candidates.is_empty() && self.anagram_fallback {
+            candidates = self.find_anagrams(lower_word.clone(), false)?.into_iter()
+                .map(|(word, _)| {
+                    let distance = distance::damerau_levenshtein(&lower_word, &word);
+                    (word, distance)
+                }).collect();
+        }
+        if candidates.is_empty() {
+            if let Some(index) = &self.phonetic_index {
+                candidates = index.lookup(&lower_word).into_iter()
+                    .filter(|word| !self.personal.is_removed(word))
+                    .map(|word| {
+                        let distance = distance::damerau_levenshtein(&lower_word, &word);
+                        (word, distance)
+                    }).collect();
             }
         }
+
+        let mut suggestions = self.rank(&lower_word, candidates, n);
+        for suggestion in suggestions.iter_mut() {
+            suggestion.word = self.restore_case(&input_word, &suggestion.word);
+        }
+        Ok(suggestions)
     }
 
-    fn alternate_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
-        for i in 0..(word.chars().count()) {
-            for c in 'a'..'z' {
-                let mut new_word = Box::new(word.clone());
-                
-                new_word.replace_range(i..i+1, &c.to_string());
+    // Writes the word with the capitals the dictionary insists on, otherwise the way the input is
+    // capitalized, so a correction can replace the input without retyping
+    fn restore_case(&self, input_word: &str, lower_word: &str) -> String {
+        let input_casing = casing::detect(input_word);
+        match self.get_capitalization(lower_word) {
+            Some(word) if input_casing == casing::Casing::Upper => word.to_uppercase(),
+            Some(word) => word.clone(),
+            None => casing::apply(input_casing, lower_word),
+        }
+    }
 
-                words.insert(new_word);
+    // Every word of the text missing from the dictionary, or lacking the capitals it insists on,
+    // with up to n suggestions each
+    pub fn check_text(&mut self, text: String, n: usize) -> Result<Vec<Misspelling>, SpellError> {
+        // A hyphenated word the dictionary has whole, like "well-known", isn't checked part by part
+        let known_compounds: Vec<(usize, usize)> = tokenizer::split_entries(&text).into_iter()
+            .filter(|entry| entry.get_word().contains('-') && self.is_known_word(entry.get_word().to_string()))
+            .map(|entry| (entry.get_start(), entry.get_end()))
+            .collect();
+
+        let mut out = Vec::new();
+        for token in tokenizer::tokenize(&text) {
+            if known_compounds.iter().any(|(start, end)| *start <= token.get_start() && token.get_end() <= *end) {
+                continue;
+            }
+            let lower_word = token.get_word().to_lowercase();
+            let miscapitalized = match self.get_capitalization(&lower_word) {
+                Some(word) => token.get_word() != word && token.get_word() != word.to_uppercase(),
+                None => false,
+            };
+            let ignored = self.personal.is_ignored(&lower_word);
+            if !ignored && (miscapitalized || !self.is_known_word(lower_word)) {
+                let suggestions = self.suggest(token.get_word().to_string(), n)?;
+                out.push(Misspelling {token, suggestions});
             }
         }
+        Ok(out)
     }
 
-    fn insert_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
-        for i in 0..=(word.chars().count()) {
-            for c in 'a'..='z' {
-                let mut new_word = Box::new(word.clone());
+    // Corrects every word of the sentence at once, choosing the sequence of suggestions that best
+    // fits the context model. Words in the dictionary are weighed against their neighbours too,
+    // so a real word can still be replaced when the context calls for another
+    pub fn correct_sentence(&mut self, sentence: String) -> Result<String, SpellError> {
+        if sentence.trim().is_empty() {
+            return Err(SpellError::EmptyInput);
+        }
+        if let Some(letter) = sentence.chars().find(|letter| letter.is_control() && !letter.is_whitespace()) {
+            return Err(SpellError::UnsupportedCharacter(letter));
+        }
+        // Punctuation, numbers and the like are left where they are, and only words are corrected
+        let tokens = tokenizer::tokenize(&sentence);
+        if tokens.is_empty() {
+            return Ok(sentence);
+        }
 
-                new_word.insert(i, c);
+        // Each word's options as written and in lowercase, with the log probability of typing the
+        // word as each of them
+        let mut lattice: Vec<Vec<(String, String, f64)>> = Vec::new();
+        for token in tokens.iter() {
+            let token = token.get_word();
+            let lower_token = token.to_lowercase();
+            let mut options: Vec<(String, String, f64)> = self.suggest(token.to_string(), SENTENCE_CANDIDATES)?.into_iter().map(|suggestion| {
+                let lower_word = suggestion.word.to_lowercase();
+                let channel = match &self.error_model {
+                    Some(model) => model.channel_probability(&lower_token, &lower_word),
+                    None => EDIT_PROBABILITY.powf(suggestion.cost),
+                };
+                (suggestion.word, lower_word, channel.ln())
+            }).collect();
+            if options.is_empty() {
+                options.push((token.to_string(), lower_token, 0.0));
+            }
+            lattice.push(options);
+        }
 
-                words.insert(new_word);
+        // Viterbi over trigrams: only the best path ending in each pair of options can be extended
+        // into the best sentence, so keep one path per pair
+        let mut paths: Vec<(f64, Vec<usize>)> = vec![(0.0, Vec::new())];
+        for (i, options) in lattice.iter().enumerate() {
+            let mut best: HashMap<(Option<usize>, usize), (f64, Vec<usize>)> = HashMap::new();
+            for (score, path) in paths.iter() {
+                let prev1 = if i > 0 {lattice[i - 1][path[i - 1]].1.as_str()} else {context::START};
+                let prev2 = if i > 1 {lattice[i - 2][path[i - 2]].1.as_str()} else {context::START};
+
+                for (j, (_, word, channel)) in options.iter().enumerate() {
+                    let fit = match &self.context_model {
+                        Some(model) => model.probability(prev2, prev1, word).ln(),
+                        None => 0.0,
+                    };
+                    let next_score = score + fit + channel;
+                    let key = (path.last().copied(), j);
+                    if best.get(&key).is_none_or(|(best_score, _)| next_score > *best_score) {
+                        let mut next_path = path.clone();
+                        next_path.push(j);
+                        best.insert(key, (next_score, next_path));
+                    }
+                }
             }
+            paths = best.into_values().collect();
+            paths.sort_by(|a, b| a.1.cmp(&b.1));
         }
 
+        // Ties go to the higher ranked suggestions
+        let (_, path) = paths.into_iter()
+            .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)))
+            .unwrap();
+
+        // Each correction goes back in place of the word it replaces, and everything between the
+        // words stays as it was
+        let mut out = String::with_capacity(sentence.len());
+        let mut end = 0;
+        for (i, (token, j)) in tokens.iter().zip(path.iter()).enumerate() {
+            let word = &lattice[i][*j].0;
+            out.push_str(&sentence[end..token.get_start()]);
+            match word == token.get_word() {
+                true => out.push_str(&sentence[token.get_start()..token.get_end()]),
+                false => out.push_str(word),
+            }
+            end = token.get_end();
+        }
+        out.push_str(&sentence[end..]);
+        Ok(out)
     }
 
-    fn gen_edit_dist1(&mut self, edit_dist1: &mut HashSet<Box<String>>, word: String)  {
-        self.delete_char(edit_dist1, &word);
-        self.transpose_char(edit_dist1, &word);
-        self.alternate_char(edit_dist1, &word);
-        self.insert_char(edit_dist1, &word);
+    fn edit_candidates(&mut self, lower_word: String, n: usize) -> Vec<(String, u32)> {
+        let mut candidates: Vec<(String, u32)> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut edits: HashSet<Box<String>> = HashSet::new();
+        edits.insert(Box::new(lower_word.clone()));
+
+        for pass in 0..=self.max_distance {
+            if pass > 0 {
+                let mut next_edits : HashSet<Box<String>> = HashSet::new();
+                self.gen_edits(&mut next_edits, &edits);
+                //println!("edit dist {} size: {}",pass,next_edits.len());
+                edits = next_edits;
+            }
 
+            for word in edits.iter() {
+                if self.get_word_freq(word).is_some() && !self.personal.is_removed(word) && seen.insert(word.to_string()) {
+                    // Chained edits can reach a word more cheaply than a single alignment allows,
+                    // so measure it the same way the trie walk does
+                    let distance = distance::damerau_levenshtein(&lower_word, word);
+                    if distance <= self.max_distance {
+                        candidates.push((word.to_string(), distance));
+                    }
+                }
+            }
 
-/*
-        for new_word in self.delete_char(&word) {
-            edit_dist1.insert(new_word);
+            // Discounted keys let a further word undercut a nearer one, so only stop early when
+            // every edit costs the same
+            if self.keyboard_layout.is_none() && candidates.iter().filter(|(_, distance)| *distance <= pass).count() >= n {
+                break;
+            }
         }
-        for new_word in self.transpose_char(&word) {
-            edit_dist1.insert(new_word);
+
+        candidates
+    }
+
+    // Orders by edit cost, then frequency, then alphabetically, and keeps the best n. Without a
+    // keyboard layout the cost is just the edit distance. An error model replaces all of this
+    // with the probability of the word having been typed as the input
+    fn rank(&self, lower_word: &str, candidates: Vec<(String, u32)>, n: usize) -> Vec<Suggestion> {
+        let mut suggestions: Vec<Suggestion> = candidates.into_iter().filter_map(|(word, distance)| {
+            let frequency = self.get_word_freq(&word)?;
+            let cost = match self.keyboard_layout {
+                Some(layout) => distance::weighted_damerau_levenshtein(lower_word, &word, |letter1, letter2| layout.substitution_cost(letter1, letter2)),
+                None => distance as f64,
+            };
+            let probability = match &self.error_model {
+                Some(model) => frequency as f64 / (self.total_freq + self.personal_words.get_word_count() as u64) as f64 * model.channel_probability(lower_word, &word),
+                None => 0.0,
+            };
+            Some(Suggestion {word, distance, cost, frequency, probability, score: 0.0})
+        }).collect();
+
+        if self.error_model.is_some() {
+            let total_probability: f64 = suggestions.iter().map(|suggestion| suggestion.probability).sum();
+            suggestions.sort_by(|a, b| b.probability.total_cmp(&a.probability).then(a.word.cmp(&b.word)));
+            suggestions.truncate(n);
+            // Each word's share of the probability over every candidate found, not just the best n
+            for suggestion in suggestions.iter_mut() {
+                suggestion.score = suggestion.probability / total_probability;
+            }
+            return suggestions;
         }
-        for new_word in self.alternate_char(&word) {
-            edit_dist1.insert(new_word);
+
+        suggestions.sort_by(|a, b| {
+            a.cost.total_cmp(&b.cost)
+                .then(b.frequency.cmp(&a.frequency))
+                .then(a.word.cmp(&b.word))
+        });
+        suggestions.truncate(n);
+
+        // Costs only come in multiples of the cheapest edit, and frequency never moves a word by
+        // more than one of those steps, so the score follows the ordering
+        let step = match self.keyboard_layout {
+            Some(_) => keyboard::ADJACENT_KEY_COST,
+            None => 1.0,
+        };
+        // Phonetic matches can lie beyond max_distance, so scale by whichever is further
+        let max_cost = suggestions.iter().map(|suggestion| suggestion.cost).fold(self.max_distance as f64, f64::max);
+        let max_freq = suggestions.iter().map(|suggestion| suggestion.frequency).max().unwrap_or(1);
+        for suggestion in suggestions.iter_mut() {
+            let freq_share = suggestion.frequency as f64 / max_freq as f64;
+            suggestion.score = (max_cost - suggestion.cost + freq_share * step) / (max_cost + step);
         }
-        for new_word in self.insert_char(&word) {
-            edit_dist1.insert(new_word);
-        }*/
+
+        suggestions
     }
 
-    fn gen_edit_dist2(&mut self, edit_dist2: &mut HashSet<Box<String>>, words: &HashSet<Box<String>>) {
+    fn delete_char(&mut self,words: &mut HashSet<Box<String>> , word: & String) {
+        //println!("length of word {}",word.chars().count());
+        let letters: Vec<char> = word.chars().collect();
+        for i in 0..(letters.len()) {
+            let mut new_word = letters.clone();
+            new_word.remove(i);
+            
+            words.insert(Box::new(new_word.into_iter().collect()));
+        }
+    }
 
-        for word in words.iter() {
-            self.delete_char(edit_dist2, &word);
-            self.transpose_char(edit_dist2, &word);
-            self.alternate_char(edit_dist2, &word);
-            self.insert_char(edit_dist2, &word);
-        }
-        /*for word in words.iter() {
-            for new_word in self.delete_char(&word) {
-                edit_dist2.insert(new_word);
-            }
-            for new_word in self.transpose_char(&word) {
-                edit_dist2.insert(new_word);
+    fn transpose_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
+        let letters: Vec<char> = word.chars().collect();
+        for i in 1..(letters.len()) {
+            if letters[i - 1] == letters[i] {
+                continue;
             }
-            for new_word in self.alternate_char(&word) {
-                edit_dist2.insert(new_word);
+            let mut new_word = letters.clone();
+
+            new_word.swap(i - 1, i);
+            //println!("\t{} {:?}",word,new_word);
+            words.insert(Box::new(new_word.into_iter().collect()));
+        }
+    }
+
+    fn alternate_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
+        let letters: Vec<char> = word.chars().collect();
+        for i in 0..(letters.len()) {
+            for c in self.alphabet.iter() {
+                let mut new_word = letters.clone();
+                
+                new_word[i] = *c;
+
+                words.insert(Box::new(new_word.into_iter().collect()));
             }
-            for new_word in self.insert_char(&word) {
-                edit_dist2.insert(new_word);
+        }
+    }
+
+    fn insert_char(&mut self,words: &mut HashSet<Box<String>> ,word: &String) {
+        let letters: Vec<char> = word.chars().collect();
+        for i in 0..=(letters.len()) {
+            for c in self.alphabet.iter() {
+                let mut new_word = letters.clone();
+
+                new_word.insert(i, *c);
+
+                words.insert(Box::new(new_word.into_iter().collect()));
             }
-        }*/
+        }
+
+    }
+
+    fn gen_edits(&mut self, edits: &mut HashSet<Box<String>>, words: &HashSet<Box<String>>) {
+        for word in words.iter() {
+            self.delete_char(edits, word);
+            self.transpose_char(edits, word);
+            self.alternate_char(edits, word);
+            self.insert_char(edits, word);
+        }
     }
     
 }
 
+// Words may hold any character except whitespace and control characters
+fn check_word(word: &str) -> Result<(), SpellError> {
+    if word.is_empty() {
+        return Err(SpellError::EmptyInput);
+    }
+    match word.chars().find(|letter| letter.is_whitespace() || letter.is_control()) {
+        Some(letter) => Err(SpellError::UnsupportedCharacter(letter)),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -229,19 +964,35 @@ mod tests {
     const WORD_FILENAME :&str = "word.txt";
     const WORDS_FILENAME :&str = "words.txt";
     const BIG_FILENAME :&str = "notsobig.txt";
+    const UNICODE_FILENAME :&str = "unicode.txt";
+    const KEYBOARD_FILENAME :&str = "keyboard.txt";
+    const PHONETIC_FILENAME :&str = "phonetic.txt";
+    const TYPOS_FILENAME :&str = "typos.txt";
+    const CONTEXT_FILENAME :&str = "context.txt";
+    const PROSE_FILENAME :&str = "prose.txt";
+    const NAMES_FILENAME :&str = "names.txt";
+    const ANAGRAMS_FILENAME :&str = "anagrams.txt";
     const WORD :&str = "yea";
 
     fn setup() -> SpellCorrector {
         return SpellCorrector::new();
     }
 
+    const BACKENDS :[Backend; 4] = [Backend::TrieWalk, Backend::EditGeneration, Backend::SymSpell, Backend::BkTree];
+
     fn test(filename: &str, word: &str, corrector: &mut SpellCorrector) -> Option<String> {
         let suggestion;
 
-        corrector.use_dictionary(filename.to_string());
+        corrector.use_dictionary(filename.to_string()).expect("Unable to find dictionary");
 
         suggestion = corrector.suggest_similar_word(word.to_string());
 
+        for backend in BACKENDS {
+            let mut other = corrector.clone();
+            other.set_backend(backend);
+            assert_eq!(suggestion.as_ref().ok(), other.suggest_similar_word(word.to_string()).as_ref().ok(), "{:?} backend disagrees on \"{}\"", backend, word);
+        }
+
         match suggestion {
             Ok(word) => return Some(word),
             Err(_v) => return None,
@@ -263,7 +1014,7 @@ mod tests {
         assert_eq!(WORD,suggested_word.as_ref().unwrap(), "Lower case of expected word.");
         
         let suggested_word = test(WORD_FILENAME, &WORD.to_uppercase(), &mut corrector);
-        assert_eq!(WORD,suggested_word.as_ref().unwrap(), "Upper case of expected word.");
+        assert_eq!(WORD.to_uppercase(),suggested_word.unwrap(), "Upper case of expected word.");
         
         let suggested_word = test(WORD_FILENAME, WORD, &mut corrector);
         assert_eq!(WORD,suggested_word.as_ref().unwrap(), "Same spelling of expected word.");
@@ -640,13 +1391,639 @@ mod tests {
         assert_eq!("yeah",suggested_word.as_ref().unwrap(), "Choosing first word alphabetically when equal frequency");    
     }
      
+    #[test]
+    fn test_suggest_ranked() {
+        let mut corrector = setup();
+        corrector.use_dictionary(WORDS_FILENAME.to_string()).unwrap();
+
+        let suggestions = corrector.suggest("yeaz".to_string(), 3).unwrap();
+        let words: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.get_word()).collect();
+        assert_eq!(vec!["yeah", "yea", "yeahs"], words, "Suggestions not ordered by distance, then frequency");
+        assert_eq!(1, suggestions[0].get_distance(), "Incorrect distance for \"yeah\"");
+        assert_eq!(4, suggestions[0].get_freq(), "Incorrect frequency for \"yeah\"");
+        assert_eq!(2, suggestions[2].get_distance(), "Incorrect distance for \"yeahs\"");
+        assert!(suggestions.windows(2).all(|pair| pair[0].get_score() > pair[1].get_score()), "Scores do not follow the ranking");
+        assert!(suggestions.iter().all(|suggestion| suggestion.get_score() > 0.0 && suggestion.get_score() <= 1.0), "Scores not normalized");
+
+        let suggestions = corrector.suggest("yea".to_string(), 2).unwrap();
+        assert_eq!(2, suggestions.len(), "Incorrect number of suggestions");
+        assert_eq!("yea", suggestions[0].get_word(), "Exact match not ranked first");
+        assert_eq!(0, suggestions[0].get_distance(), "Exact match has non-zero distance");
+        assert_eq!("yeah", suggestions[1].get_word(), "Closest word not ranked after exact match");
+        assert!(suggestions[0].get_score() > suggestions[1].get_score(), "Exact match scored below a more frequent distance one word");
+
+        assert!(corrector.suggest("yeaz".to_string(), 0).unwrap().is_empty(), "Asked for no suggestions but got some");
+        assert!(corrector.suggest("abcdefghijklmnopqrstuvqxyz".to_string(), 5).unwrap().is_empty(), "Got suggestions for dissimilar word");
+    }
+
+    #[test]
+    fn test_max_distance() {
+        let mut corrector = setup();
+        corrector.set_max_distance(1);
+
+        let suggested_word = test(WORD_FILENAME, "ye", &mut corrector);
+        assert_eq!(WORD,suggested_word.as_ref().unwrap(), "Distance one word not found with max distance one");
+
+        let suggested_word = test(WORD_FILENAME, "y", &mut corrector);
+        assert!(suggested_word.is_none(), "Found distance two word with max distance one");
+
+        let suggested_word = test(WORD_FILENAME, "qqq", &mut corrector);
+        assert!(suggested_word.is_none(), "Found distance three word with max distance one");
+
+        corrector.set_max_distance(3);
+        let suggested_word = test(WORD_FILENAME, "qqq", &mut corrector);
+        assert_eq!(WORD,suggested_word.as_ref().unwrap(), "Distance three word not found with max distance three");
+
+        let suggestions = corrector.suggest("qqq".to_string(), 1).unwrap();
+        assert_eq!(3, suggestions[0].get_distance(), "Incorrect distance for \"qqq\"");
+
+        corrector.set_max_distance(0);
+        let suggested_word = test(WORD_FILENAME, "ye", &mut corrector);
+        assert!(suggested_word.is_none(), "Found misspelled word with max distance zero");
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let mut correctors: Vec<SpellCorrector> = BACKENDS.iter().map(|backend| {
+            let mut corrector = setup();
+            corrector.set_backend(*backend);
+            corrector
+        }).collect();
+
+        let guesses = ["yea", "ye", "yeaz", "yeahj", "flaot", "flobt", "bloat", "reah", "yhb", "eay", "aye", "ycae", "lol", ""];
+        for filename in [WORD_FILENAME, WORDS_FILENAME, UNICODE_FILENAME] {
+            for max_distance in 1..=2 {
+                for corrector in correctors.iter_mut() {
+                    corrector.use_dictionary(filename.to_string()).unwrap();
+                    corrector.set_max_distance(max_distance);
+                }
+                for guess in guesses {
+                    let expected = correctors[0].suggest(guess.to_string(), 10).ok();
+                    for corrector in correctors.iter_mut().skip(1) {
+                        assert_eq!(expected, corrector.suggest(guess.to_string(), 10).ok(),
+                                   "{:?} backend disagrees on \"{}\" in {} at max distance {}", corrector.get_backend(), guess, filename, max_distance);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_symspell_index_follows_max_distance() {
+        let mut corrector = setup();
+        corrector.set_backend(Backend::SymSpell);
+        corrector.set_max_distance(1);
+
+        let suggested_word = test(WORD_FILENAME, "y", &mut corrector);
+        assert!(suggested_word.is_none(), "Found distance two word with max distance one");
+
+        corrector.set_max_distance(3);
+        let suggestions = corrector.suggest("qqq".to_string(), 1).unwrap();
+        assert_eq!(WORD, suggestions[0].get_word(), "Index not rebuilt after raising max distance");
+        assert_eq!(3, suggestions[0].get_distance(), "Incorrect distance for \"qqq\"");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut corrector = setup();
+
+        let result = corrector.use_dictionary("missing.txt".to_string());
+        assert!(matches!(result, Err(SpellError::Io(_))), "Missing dictionary did not report an I/O error");
+
+        corrector.use_dictionary(WORD_FILENAME.to_string()).unwrap();
+        assert!(corrector.use_dictionary("missing.txt".to_string()).is_err(), "Missing dictionary did not report an error");
+        assert_eq!(WORD, corrector.suggest_similar_word("ye".to_string()).unwrap(), "Failed load discarded the previous dictionary");
+
+        let result = corrector.suggest_similar_word("".to_string());
+        assert!(matches!(result, Err(SpellError::EmptyInput)), "Empty input not reported as such");
+
+        let result = corrector.suggest_similar_word("ye a".to_string());
+        assert!(matches!(result, Err(SpellError::UnsupportedCharacter(' '))), "Space in input not reported as unsupported");
+
+        let result = corrector.suggest("ye\ta".to_string(), 3);
+        assert!(matches!(result, Err(SpellError::UnsupportedCharacter('\t'))), "Tab in input not reported as unsupported");
+
+        let result = corrector.suggest_similar_word("lol".to_string());
+        assert!(matches!(result, Err(SpellError::NoCandidate(ref word)) if word == "lol"), "Dissimilar word not reported as having no candidate");
+        assert_eq!("Unable to find word \"lol\"", result.unwrap_err().to_string(), "Incorrect error message");
+    }
+
+    #[test]
+    fn test_keyboard_layout() {
+        let mut corrector = setup();
+
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("bat",suggested_word.as_ref().unwrap(), "Choosing word with higher frequency without a keyboard layout");
+
+        corrector.set_keyboard_layout(Some(KeyboardLayout::Qwerty));
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("cat",suggested_word.as_ref().unwrap(), "Choosing adjacent QWERTY key over higher frequency");
+
+        corrector.set_keyboard_layout(Some(KeyboardLayout::Azerty));
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("cat",suggested_word.as_ref().unwrap(), "Choosing adjacent AZERTY key over higher frequency");
+
+        corrector.set_keyboard_layout(Some(KeyboardLayout::Dvorak));
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("bat",suggested_word.as_ref().unwrap(), "Choosing adjacent Dvorak key over lower frequency");
+
+        corrector.set_keyboard_layout(Some(KeyboardLayout::Qwerty));
+        let suggestions = corrector.suggest("xat".to_string(), 3).unwrap();
+        let words: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.get_word()).collect();
+        assert_eq!(vec!["cat", "bat", "at"], words, "Suggestions not ordered by keyboard cost, then frequency");
+        assert_eq!(0.5, suggestions[0].get_cost(), "Adjacent key substitution not discounted");
+        assert_eq!(1, suggestions[0].get_distance(), "Keyboard layout changed the edit distance");
+        assert!(suggestions.windows(2).all(|pair| pair[0].get_score() > pair[1].get_score()), "Scores do not follow the keyboard ranking");
+
+        let suggested_word = test(WORDS_FILENAME, "flobt", &mut corrector);
+        assert_eq!("float",suggested_word.as_ref().unwrap(), "Keyboard layout broke ordinary substitution");
+    }
+
+    #[test]
+    fn test_unicode_dictionary() {
+        let mut corrector = setup();
+
+        let guess: &str = "Café"; 
+        let suggested_word = test(UNICODE_FILENAME, guess, &mut corrector);
+        assert_eq!("Café",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "Café",&suggested_word.as_ref().unwrap()));
+
+        let guess: &str = "cafe"; 
+        let suggested_word = test(UNICODE_FILENAME, guess, &mut corrector);
+        assert_eq!("café",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "café",&suggested_word.as_ref().unwrap()));
+
+        let guess: &str = "dont"; 
+        let suggested_word = test(UNICODE_FILENAME, guess, &mut corrector);
+        assert_eq!("don't",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "don't",&suggested_word.as_ref().unwrap()));
+
+        let guess: &str = "zazolc"; 
+        let suggested_word = test(UNICODE_FILENAME, guess, &mut corrector);
+        assert!(suggested_word.is_none(), "Guessed word four accents away from \"zażółć\"");
+
+        let guess: &str = "jazń"; 
+        let suggested_word = test(UNICODE_FILENAME, guess, &mut corrector);
+        assert_eq!("jaźń",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "jaźń",&suggested_word.as_ref().unwrap()));
+        assert!(corrector.is_known_word("1st".to_string()), "Word with a digit dropped from the dictionary");
+
+        let path = std::env::temp_dir().join(format!("spelling_corrector_entries_{}.txt", std::process::id()));
+        fs::write(&path, "peut-être, well-known.").unwrap();
+        corrector.use_dictionary(path.to_string_lossy().to_string()).unwrap();
+        assert!(corrector.is_known_word("peut-être".to_string()) && corrector.is_known_word("well-known".to_string()), "Hyphenated word not loaded whole");
+        assert!(!corrector.is_known_word("peut".to_string()), "Hyphenated word split");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_error_model() {
+        let mut corrector = setup();
+
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("bat",suggested_word.as_ref().unwrap(), "Choosing word with higher frequency without an error model");
+
+        corrector.use_error_model(TYPOS_FILENAME.to_string()).expect("Unable to find error model");
+        assert!(corrector.has_error_model(), "Error model not loaded");
+
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("cat",suggested_word.as_ref().unwrap(), "Common substitution did not outweigh frequency");
+
+        let suggestions = corrector.suggest("xat".to_string(), 3).unwrap();
+        let words: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.get_word()).collect();
+        assert_eq!(vec!["cat", "bat", "at"], words, "Suggestions not ordered by probability");
+        assert!(suggestions.windows(2).all(|pair| pair[0].get_probability() > pair[1].get_probability()), "Probabilities do not follow the ordering");
+        let total: f64 = suggestions.iter().map(|suggestion| suggestion.get_score()).sum();
+        assert!((total - 1.0).abs() < 1e-9, "Scores of every candidate do not sum to one");
+
+        let suggested_word = test(KEYBOARD_FILENAME, "bat", &mut corrector);
+        assert_eq!("bat",suggested_word.as_ref().unwrap(), "Correct word not preferred with an error model");
+
+        let result = corrector.use_error_model(WORDS_FILENAME.to_string());
+        assert!(matches!(result, Err(SpellError::MalformedLine(1))), "Line without a pair not reported");
+        assert!(corrector.has_error_model(), "Failed load discarded the previous error model");
+
+        corrector.clear_error_model();
+        let suggested_word = test(KEYBOARD_FILENAME, "xat", &mut corrector);
+        assert_eq!("bat",suggested_word.as_ref().unwrap(), "Error model still used after it was cleared");
+    }
+
+    #[test]
+    fn test_case_restoration() {
+        let mut corrector = setup();
+
+        for (guess, expected) in [("jasn", "Jason"), ("JASN", "JASON"), ("nasa", "NASA"), ("Nasa", "NASA"), ("iphone", "iPhone"), ("IPHONE", "IPHONE"),
+                                  ("pariss", "paris"), ("Pariss", "Paris"), ("PARISS", "PARIS"), ("Aple", "Apple"), ("aPPle", "apple"), ("THE", "THE"), ("the", "the")] {
+            let suggested_word = test(NAMES_FILENAME, guess, &mut corrector);
+            assert_eq!(expected,suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, expected,&suggested_word.as_ref().unwrap()));
+        }
+
+        let misspellings = corrector.check_text("nasa and iPhone and Jason and jason and NASA and Iphone".to_string(), 1).unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|misspelling| misspelling.get_word()).collect();
+        assert_eq!(vec!["nasa", "jason", "Iphone"], words, "Words missing their mandatory capitals not reported");
+        assert_eq!("NASA", misspellings[0].get_suggestions()[0].get_word(), "Mandatory capitals not suggested");
+        assert_eq!("iPhone", misspellings[2].get_suggestions()[0].get_word(), "Mandatory capitals not suggested");
+
+        let misspellings = corrector.check_text("The end. Paris and paris".to_string(), 1).unwrap();
+        assert!(misspellings.is_empty(), "Word capitalized both ways or opening a sentence reported as miscapitalized");
+    }
+
+    #[test]
+    fn test_personal_dictionary() {
+        let dir = std::env::temp_dir().join(format!("spelling_corrector_personal_{}", std::process::id()));
+        let path = dir.join("personal.txt");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut corrector = setup();
+        corrector.use_personal_dictionary(&path).expect("Missing personal dictionary not treated as empty");
+
+        corrector.add_word("yeet".to_string()).unwrap();
+        corrector.add_word("Rustacean".to_string()).unwrap();
+        assert!(corrector.is_known_word("YEET".to_string()), "Added word not known");
+
+        let suggested_word = test(WORDS_FILENAME, "yeett", &mut corrector);
+        assert_eq!("yeet",suggested_word.as_ref().unwrap(), "Added word not suggested after loading a dictionary");
+        let suggested_word = test(WORDS_FILENAME, "rustacen", &mut corrector);
+        assert_eq!("Rustacean",suggested_word.as_ref().unwrap(), "Added word lost its capitals");
+
+        corrector.remove_word("yea".to_string()).unwrap();
+        assert!(!corrector.is_known_word("yea".to_string()), "Removed dictionary word still known");
+        let suggested_word = test(WORDS_FILENAME, "yea", &mut corrector);
+        assert_eq!("yeah",suggested_word.as_ref().unwrap(), "Removed word still suggested");
+
+        corrector.ignore_word("flobt".to_string()).unwrap();
+        assert!(corrector.is_known_word("flobt".to_string()), "Ignored word not known");
+        assert_eq!("Flobt", corrector.suggest_similar_word("Flobt".to_string()).unwrap(), "Ignored word not left alone");
+
+        let misspellings = corrector.check_text("yea yeet flobt Rustacean floot".to_string(), 1).unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|misspelling| misspelling.get_word()).collect();
+        assert_eq!(vec!["yea", "floot"], words, "Personal dictionary not honoured when checking text");
+
+        let mut reloaded = setup();
+        reloaded.use_dictionary(WORDS_FILENAME.to_string()).unwrap();
+        reloaded.use_personal_dictionary(&path).unwrap();
+        assert!(reloaded.is_known_word("yeet".to_string()), "Added word not persisted");
+        assert!(!reloaded.is_known_word("yea".to_string()), "Removed word not persisted");
+        assert!(!reloaded.is_known_word("flobt".to_string()), "Ignored word persisted past the session");
+
+        reloaded.add_word("yea".to_string()).unwrap();
+        assert!(reloaded.is_known_word("yea".to_string()), "Adding a removed word did not restore it");
+
+        assert!(matches!(reloaded.add_word("".to_string()), Err(SpellError::EmptyInput)), "Empty word added");
+        assert!(matches!(reloaded.add_word("a b".to_string()), Err(SpellError::UnsupportedCharacter(' '))), "Word with a space added");
+
+        // A file where the directory should be makes every save fail
+        fs::remove_dir_all(&dir).unwrap();
+        fs::write(&dir, "not a directory").unwrap();
+        assert!(matches!(reloaded.add_word("zorp".to_string()), Err(SpellError::Write(_))), "Failed save not reported");
+        assert!(!reloaded.is_known_word("zorp".to_string()), "Word known after a failed save");
+        assert!(reloaded.remove_word("yeet".to_string()).is_err(), "Failed save not reported");
+        assert!(reloaded.is_known_word("yeet".to_string()), "Word removed after a failed save");
+
+        fs::remove_file(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut corrector = setup();
+        corrector.use_dictionary(WORDS_FILENAME.to_string()).unwrap();
+
+        assert_eq!(vec!["yeah", "yeahs", "yea"], corrector.complete("ye".to_string(), 5).unwrap(), "Completions not ranked by frequency");
+        assert_eq!(vec!["YEAH", "YEAHS"], corrector.complete("YE".to_string(), 2).unwrap(), "Completions not capitalized like the prefix");
+        assert!(corrector.complete("zz".to_string(), 5).unwrap().is_empty(), "Completed an unknown prefix");
+        assert!(matches!(corrector.complete("".to_string(), 5), Err(SpellError::EmptyInput)), "Completed an empty prefix");
+
+        corrector.set_completion_cache(true);
+        corrector.use_dictionary(WORDS_FILENAME.to_string()).unwrap();
+        assert!(corrector.has_completion_cache(), "Completion cache lost on loading a dictionary");
+        corrector.remove_word("yeah".to_string()).unwrap();
+        corrector.add_word("yeet".to_string()).unwrap();
+        assert_eq!(vec!["yeahs", "yea"], corrector.complete("ye".to_string(), 2).unwrap(), "Removed word completed");
+        assert_eq!(vec!["yeet"], corrector.complete("yee".to_string(), 2).unwrap(), "Added word not completed");
+
+        corrector.use_dictionary(NAMES_FILENAME.to_string()).unwrap();
+        assert_eq!(vec!["iPhone"], corrector.complete("iph".to_string(), 2).unwrap(), "Mandatory capitals not kept in completions");
+    }
+
+    #[test]
+    fn test_binary_dictionary() {
+        let dir = std::env::temp_dir().join(format!("spelling_corrector_binary_{}", std::process::id()));
+        let path = dir.join("names.dict");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut corrector = setup();
+        corrector.use_dictionary(NAMES_FILENAME.to_string()).unwrap();
+        corrector.save_dictionary(&path).expect("Unable to save dictionary");
+
+        let mut reloaded = setup();
+        reloaded.load_dictionary(&path).expect("Unable to load saved dictionary");
+        assert_eq!(corrector.dictionary, reloaded.dictionary, "Dictionary changed in the round trip");
+        assert_eq!(corrector.capitalization, reloaded.capitalization, "Mandatory capitals lost in the round trip");
+        assert_eq!(corrector.total_freq, reloaded.total_freq, "Total frequency changed in the round trip");
+        assert_eq!(corrector.alphabet, reloaded.alphabet, "Alphabet changed in the round trip");
+        for backend in BACKENDS {
+            reloaded.set_backend(backend);
+            assert_eq!("iPhone", reloaded.suggest_similar_word("iphnoe".to_string()).unwrap(), "{:?} backend lost a word in the round trip", backend);
+        }
+
+        // The user's words stay out of the saved dictionary
+        let saved = fs::read(&path).unwrap();
+        let words = binary::decode(&saved).unwrap();
+        assert!(words.windows(2).all(|pair| pair[0].0 < pair[1].0), "Saved words not in sorted order");
+        let digest = corrector.get_dictionary_digest();
+        corrector.use_personal_dictionary(&dir.join("personal.txt")).unwrap();
+        corrector.add_word("Zorblax".to_string()).unwrap();
+        corrector.add_word("jason".to_string()).unwrap();
+        corrector.remove_word("NASA".to_string()).unwrap();
+        corrector.save_dictionary(&path).unwrap();
+        assert_eq!(saved, fs::read(&path).unwrap(), "Personal changes saved with the dictionary");
+        assert_eq!(digest, corrector.get_dictionary_digest(), "Personal changes altered the digest");
+        assert!(corrector.is_known_word("zorblax".to_string()), "Added word not known");
+        assert_eq!("Zorblax", corrector.suggest_similar_word("zorblx".to_string()).unwrap(), "Added word lost its capitals");
+
+        fs::write(&path, "Jason NASA").unwrap();
+        assert!(matches!(reloaded.load_dictionary(&path), Err(SpellError::InvalidDictionary(_))), "Text file loaded as a binary dictionary");
+        assert!(matches!(reloaded.load_dictionary(&dir.join("missing.dict")), Err(SpellError::Io(_))), "Missing file loaded");
+        assert!(matches!(reloaded.save_dictionary(&dir.join("missing").join("names.dict")), Err(SpellError::Write(_))), "Failed save not reported as a write error");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dictionary_digest() {
+        let dir = std::env::temp_dir().join(format!("spelling_corrector_digest_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut corrector = setup();
+        corrector.use_dictionary(NAMES_FILENAME.to_string()).unwrap();
+        let digest = corrector.get_dictionary_digest();
+        corrector.save_dictionary(&dir.join("names.dict")).unwrap();
+        corrector.save_mapped_dictionary(&dir.join("names.map")).unwrap();
+
+        let mut other = setup();
+        other.load_dictionary(&dir.join("names.dict")).unwrap();
+        assert_eq!(digest, other.get_dictionary_digest(), "Digest changed in a binary round trip");
+        other.use_mapped_dictionary(&dir.join("names.map")).unwrap();
+        assert_eq!(digest, other.get_dictionary_digest(), "Digest of a mapped dictionary differs");
+
+        corrector.minimize_dictionary();
+        assert_eq!(digest, corrector.get_dictionary_digest(), "Digest of a minimized dictionary differs");
+        corrector.add_word("Aardvark".to_string()).unwrap();
+        assert_eq!(digest, corrector.get_dictionary_digest(), "Added word changed the digest");
+        other.use_dictionary(PROSE_FILENAME.to_string()).unwrap();
+        assert_ne!(digest, other.get_dictionary_digest(), "Different dictionaries return same digest");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_dictionary() {
+        let dir = std::env::temp_dir().join(format!("spelling_corrector_mapped_dictionary_{}", std::process::id()));
+        let path = dir.join("names.map");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut corrector = setup();
+        corrector.use_dictionary(NAMES_FILENAME.to_string()).unwrap();
+        corrector.save_mapped_dictionary(&path).expect("Unable to save mapped dictionary");
+
+        let mut mapped = setup();
+        mapped.use_mapped_dictionary(&path).expect("Unable to map dictionary");
+        assert_eq!(corrector.capitalization, mapped.capitalization, "Mandatory capitals lost in the mapped dictionary");
+        assert_eq!(corrector.total_freq, mapped.total_freq, "Total frequency differs in the mapped dictionary");
+        assert_eq!(corrector.alphabet, mapped.alphabet, "Alphabet differs in the mapped dictionary");
+        for backend in BACKENDS {
+            mapped.set_backend(backend);
+            for guess in ["iphnoe", "jasn", "Pariss", "NAS", "teh"] {
+                corrector.set_backend(backend);
+                assert_eq!(corrector.suggest(guess.to_string(), 3).unwrap(), mapped.suggest(guess.to_string(), 3).unwrap(), "{:?} backend differs on the mapped dictionary for \"{}\"", backend, guess);
+            }
+        }
+        assert_eq!(corrector.complete("pa".to_string(), 3).unwrap(), mapped.complete("pa".to_string(), 3).unwrap(), "Completions differ on the mapped dictionary");
+
+        mapped.add_word("Jasmine".to_string()).unwrap();
+        assert!(mapped.is_known_word("jasmine".to_string()), "Word added on top of the mapped dictionary not known");
+        assert_eq!("Jasmine", mapped.suggest_similar_word("jasmin".to_string()).unwrap(), "Word added on top of the mapped dictionary not suggested");
+        assert_eq!(vec!["Jasmine", "Jason"], mapped.complete("jas".to_string(), 3).unwrap(), "Completions not merged with added words");
+        mapped.save_mapped_dictionary(&path).unwrap();
+        let mut remapped = setup();
+        remapped.use_mapped_dictionary(&path).unwrap();
+        assert!(!remapped.is_known_word("Jasmine".to_string()), "Added word saved with the mapped dictionary");
+
+        assert!(matches!(remapped.use_mapped_dictionary(&dir.join("missing.map")), Err(SpellError::Io(_))), "Missing file mapped");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_minimize_dictionary() {
+        let mut corrector = setup();
+        corrector.use_dictionary(PROSE_FILENAME.to_string()).unwrap();
+        let original = corrector.clone();
+
+        let stats = corrector.minimize_dictionary();
+        assert_eq!(original.dictionary.get_node_count(), stats.get_nodes_before(), "Incorrect node count before minimizing");
+        assert!(stats.get_nodes_after() < stats.get_nodes_before(), "Minimizing saved no nodes ({} of {})", stats.get_nodes_after(), stats.get_nodes_before());
+        assert_eq!(0, corrector.dictionary.get_word_count(), "Words left in the trie after minimizing");
+        assert_eq!(original.dictionary.iter().collect::<Vec<_>>(), corrector.dictionary_words().collect::<Vec<_>>(), "Words or frequencies changed by minimizing");
+
+        for backend in BACKENDS {
+            let mut expected = original.clone();
+            expected.set_backend(backend);
+            corrector.set_backend(backend);
+            for guess in ["teh", "wrold", "Helo", "quikc"] {
+                assert_eq!(expected.suggest(guess.to_string(), 3).unwrap(), corrector.suggest(guess.to_string(), 3).unwrap(), "{:?} backend differs on the minimized dictionary for \"{}\"", backend, guess);
+            }
+        }
+        assert_eq!(original.complete("th".to_string(), 3).unwrap(), corrector.complete("th".to_string(), 3).unwrap(), "Completions differ on the minimized dictionary");
+
+        corrector.add_word("zyzzyva".to_string()).unwrap();
+        assert!(corrector.is_known_word("zyzzyva".to_string()), "Word added after minimizing not known");
+        let again = corrector.minimize_dictionary();
+        assert_eq!(stats.get_nodes_before(), again.get_nodes_before(), "Added word minimized with the dictionary");
+        assert!(corrector.is_known_word("zyzzyva".to_string()), "Added word lost when minimizing again");
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let mut corrector = setup();
+        corrector.use_dictionary(NAMES_FILENAME.to_string()).unwrap();
+
+        let words = |corrector: &SpellCorrector, pattern: &str| -> Vec<String> {
+            corrector.find_pattern(pattern.to_string()).unwrap().into_iter().map(|(word, _)| word).collect()
+        };
+        assert_eq!(vec!["Jason", "NASA", "paris"], words(&corrector, "?a*"), "Incorrect matches, or mandatory capitals not kept");
+        assert_eq!(vec!["and", "apple"], words(&corrector, "[a-b][^h]*"), "Incorrect matches for character classes");
+        assert_eq!(vec![("PARIS".to_string(), 2)], corrector.find_pattern("PAR*".to_string()).unwrap(), "Matches not capitalized like the pattern");
+
+        corrector.remove_word("nasa".to_string()).unwrap();
+        corrector.minimize_dictionary();
+        corrector.add_word("Aardvark".to_string()).unwrap();
+        assert_eq!(vec!["Aardvark", "Jason", "paris"], words(&corrector, "?a*"), "Added or removed words not honoured on a minimized dictionary");
+        assert!(matches!(corrector.find_pattern("[a".to_string()), Err(SpellError::InvalidPattern(_))), "Invalid pattern accepted");
+    }
+
+    #[test]
+    fn test_anagrams() {
+        let mut corrector = setup();
+        corrector.use_dictionary(ANAGRAMS_FILENAME.to_string()).unwrap();
+
+        assert_eq!(vec![("listen".to_string(), 2), ("enlist".to_string(), 1), ("silent".to_string(), 1), ("tinsel".to_string(), 1)],
+                   corrector.anagrams("silent".to_string()).unwrap(), "Incorrect anagrams, or not ranked by frequency");
+        assert_eq!(vec!["NOTES", "ONSET", "STONE", "TONES"], corrector.anagrams("TONSE".to_string()).unwrap().into_iter().map(|(word, _)| word).collect::<Vec<_>>(),
+                   "Anagrams not capitalized like the letters");
+        assert_eq!(vec!["net", "notes", "one", "onset", "stone", "ten", "tones"], corrector.sub_anagrams("stone".to_string()).unwrap().into_iter().map(|(word, _)| word).collect::<Vec<_>>(),
+                   "Incorrect sub-anagrams");
+        assert!(matches!(corrector.anagrams("".to_string()), Err(SpellError::EmptyInput)), "Found anagrams of nothing");
+
+        for backend in BACKENDS {
+            corrector.set_backend(backend);
+            corrector.set_anagram_fallback(false);
+            assert!(corrector.suggest("ltsnei".to_string(), 3).unwrap().is_empty(), "{:?} backend found a word for a scrambled input without the anagram fallback", backend);
+            corrector.set_anagram_fallback(true);
+            assert!(corrector.get_anagram_fallback(), "Anagram fallback not enabled");
+            let suggestions: Vec<String> = corrector.suggest("ltsnei".to_string(), 4).unwrap().into_iter().map(|suggestion| suggestion.word).collect();
+            let mut sorted = suggestions.clone();
+            sorted.sort();
+            assert_eq!(vec!["enlist", "listen", "silent", "tinsel"], sorted, "{:?} backend did not suggest the anagrams of a scrambled input", backend);
+        }
+
+        corrector.remove_word("listen".to_string()).unwrap();
+        corrector.minimize_dictionary();
+        corrector.add_word("Inlets".to_string()).unwrap();
+        assert_eq!(vec!["enlist", "Inlets", "silent", "tinsel"], corrector.anagrams("silent".to_string()).unwrap().into_iter().map(|(word, _)| word).collect::<Vec<_>>(),
+                   "Added or removed words not honoured on a minimized dictionary");
+    }
+
+    #[test]
+    fn test_suffixes() {
+        let mut corrector = setup();
+        corrector.use_dictionary(ANAGRAMS_FILENAME.to_string()).unwrap();
+        assert!(!corrector.has_suffix_index(), "Suffix index built without being asked for");
+
+        for enabled in [false, true] {
+            corrector.set_suffix_index(enabled);
+            assert_eq!(vec![("listen".to_string(), 2), ("ten".to_string(), 1)], corrector.ends_with("ten".to_string()).unwrap(),
+                       "Incorrect words ending with a suffix, or not ranked by frequency");
+            assert_eq!(vec!["STONES", "TONES"], corrector.ends_with("NES".to_string()).unwrap().into_iter().map(|(word, _)| word).collect::<Vec<_>>(),
+                       "Words not capitalized like the suffix");
+            assert!(corrector.ends_with("xyz".to_string()).unwrap().is_empty(), "Found words for a missing suffix");
+            assert!(matches!(corrector.ends_with("".to_string()), Err(SpellError::EmptyInput)), "Empty suffix accepted");
+
+            assert_eq!(("ten".to_string(), vec![("listen".to_string(), 2), ("ten".to_string(), 1)]), corrector.longest_common_suffix("kitten".to_string()).unwrap(),
+                       "Incorrect longest common suffix");
+            assert_eq!(("one".to_string(), vec![("one".to_string(), 1)]), corrector.longest_common_suffix("stone".to_string()).unwrap(),
+                       "Word shared a suffix with itself");
+        }
+
+        corrector.remove_word("one".to_string()).unwrap();
+        corrector.add_word("Kitten".to_string()).unwrap();
+        corrector.use_dictionary(ANAGRAMS_FILENAME.to_string()).unwrap();
+        assert!(corrector.has_suffix_index(), "Suffix index not kept across use_dictionary");
+        corrector.minimize_dictionary();
+        corrector.add_word("Mitten".to_string()).unwrap();
+        for enabled in [true, false] {
+            corrector.set_suffix_index(enabled);
+            assert_eq!(("itten".to_string(), vec![("Kitten".to_string(), 1), ("Mitten".to_string(), 1)]), corrector.longest_common_suffix("bitten".to_string()).unwrap(),
+                       "Added words not found");
+            assert_eq!(("e".to_string(), vec![("the".to_string(), 2)]), corrector.longest_common_suffix("stone".to_string()).unwrap(),
+                       "Removed word still shares a suffix");
+        }
+    }
+
+    #[test]
+    fn test_check_text() {
+        let mut corrector = setup();
+        corrector.use_dictionary(PROSE_FILENAME.to_string()).expect("Unable to find dictionary");
+
+        let prose = fs::read_to_string(PROSE_FILENAME).unwrap();
+        assert!(corrector.check_text(prose, 3).unwrap().is_empty(), "Dictionary's own text has misspellings");
+
+        for word in ["https", "example", "com", "42", "notes", "txt", "me"] {
+            assert!(corrector.dictionary.find(&word.to_string()).is_none(), "Dictionary kept \"{}\" from a URL, address, path or number", word);
+        }
+        for word in ["hello", "world", "isn't", "it's", "short", "well-known"] {
+            assert!(corrector.dictionary.find(&word.to_string()).is_some(), "Dictionary missing \"{}\" once punctuation is stripped", word);
+        }
+
+        let text = "Helo, wrld! See http://exmple.com about the fil ~/notes.txt.";
+        let misspellings = corrector.check_text(text.to_string(), 2).unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|misspelling| misspelling.get_word()).collect();
+        assert_eq!(vec!["Helo", "wrld", "See", "about", "fil"], words, "Incorrect misspelled words");
+        for misspelling in misspellings.iter() {
+            assert_eq!(misspelling.get_word(), &text[misspelling.get_start()..misspelling.get_end()], "Span does not point at the misspelling");
+        }
+        assert_eq!("Hello", misspellings[0].get_suggestions()[0].get_word(), "Incorrect suggestion for \"Helo\"");
+        assert_eq!("world", misspellings[1].get_suggestions()[0].get_word(), "Incorrect suggestion for \"wrld\"");
+        assert_eq!("file", misspellings[4].get_suggestions()[0].get_word(), "Incorrect suggestion for \"fil\"");
+        assert!(misspellings[3].get_suggestions().is_empty(), "Found suggestions for \"about\"");
+        assert!(misspellings.iter().all(|misspelling| misspelling.get_suggestions().len() <= 2), "More suggestions than asked for");
+
+        assert!(corrector.check_text("".to_string(), 3).unwrap().is_empty(), "Found misspellings in empty text");
+    }
+
+    #[test]
+    fn test_correct_sentence() {
+        let mut corrector = setup();
+        corrector.use_dictionary(CONTEXT_FILENAME.to_string()).expect("Unable to find dictionary");
+
+        assert_eq!("their is a cat", corrector.correct_sentence("their is a cat".to_string()).unwrap(), "Real word replaced without a context model");
+        assert_eq!("there is a cat", corrector.correct_sentence("there is a cst".to_string()).unwrap(), "Misspelled word not corrected without a context model");
+
+        corrector.use_context_model(CONTEXT_FILENAME.to_string()).expect("Unable to find corpus");
+        assert!(corrector.has_context_model(), "Context model not loaded");
+
+        assert_eq!("there is a cat", corrector.correct_sentence("their is a cat".to_string()).unwrap(), "Real-word error not corrected from context");
+        assert_eq!("piece of cake", corrector.correct_sentence("peace of cake".to_string()).unwrap(), "Real-word error not corrected from context");
+        assert_eq!("There is peace  in their house", corrector.correct_sentence("There is peace  in their house".to_string()).unwrap(), "Correct sentence changed");
+        assert_eq!("there is a cat on the mat.", corrector.correct_sentence("their is a cat on the mat.".to_string()).unwrap(), "Punctuation lost");
+        assert_eq!("\"there\" is a piece of cake, 42", corrector.correct_sentence("\"there\" is a peice of cake, 42".to_string()).unwrap(),
+                   "Punctuation or a number changed");
+        assert_eq!("42, 7!", corrector.correct_sentence("42, 7!".to_string()).unwrap(), "Sentence without words changed");
+        assert_eq!("they ate a piece of cake", corrector.correct_sentence("thy ate a peice of cake".to_string()).unwrap(), "Misspelled words not corrected in context");
+        assert_eq!("there is a zzzzzz", corrector.correct_sentence("there is a zzzzzz".to_string()).unwrap(), "Word without suggestions not kept");
+
+        assert!(matches!(corrector.correct_sentence(" ".to_string()), Err(SpellError::EmptyInput)), "Blank sentence not reported as empty");
+        assert!(matches!(corrector.correct_sentence("a\u{7}b".to_string()), Err(SpellError::UnsupportedCharacter('\u{7}'))), "Control character not reported as unsupported");
+
+        corrector.clear_context_model();
+        assert_eq!("their is a cat", corrector.correct_sentence("their is a cat".to_string()).unwrap(), "Context model still used after it was cleared");
+    }
+
+    #[test]
+    fn test_phonetic_fallback() {
+        let mut corrector = setup();
+        corrector.use_dictionary(PHONETIC_FILENAME.to_string()).expect("Unable to find dictionary");
+
+        assert!(corrector.suggest("nite".to_string(), 3).unwrap().is_empty(), "Found words for \"nite\" without a phonetic encoder");
+
+        corrector.set_phonetic_encoder(Some(PhoneticEncoder::DoubleMetaphone));
+        assert_eq!(Some(PhoneticEncoder::DoubleMetaphone), corrector.get_phonetic_encoder(), "Incorrect phonetic encoder");
+
+        let suggestions = corrector.suggest("nite".to_string(), 3).unwrap();
+        let words: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.get_word()).collect();
+        assert_eq!(vec!["night", "knight"], words, "Sound-alike words not ranked by distance");
+        assert_eq!(3, suggestions[0].get_distance(), "Phonetic match distance not measured");
+        assert!(suggestions[0].get_score() > suggestions[1].get_score(), "Scores do not follow the ordering");
+        assert!(suggestions[1].get_score() > 0.0, "Phonetic match beyond max distance scored at or below zero");
+
+        let suggested_word = test(PHONETIC_FILENAME, "fonetik", &mut corrector);
+        assert_eq!("phonetic",suggested_word.as_ref().unwrap(), "{}", create_error_message("fonetik", "phonetic",&suggested_word.as_ref().unwrap()));
+
+        let suggested_word = test(PHONETIC_FILENAME, "nihgt", &mut corrector);
+        assert_eq!("night",suggested_word.as_ref().unwrap(), "Phonetic match used when an edit-distance match exists");
+
+        corrector.set_phonetic_encoder(Some(PhoneticEncoder::Soundex));
+        assert!(corrector.suggest("fonetik".to_string(), 3).unwrap().is_empty(), "Soundex matched across different first letters");
+
+        corrector.set_phonetic_encoder(None);
+        assert!(corrector.suggest_similar_word("fonetik".to_string()).is_err(), "Phonetic fallback used after it was turned off");
+    }
+
     #[test]
     fn test_big_file() {
         let mut corrector = setup();
 
         let guess: &str = "Jason"; 
         let suggested_word = test(BIG_FILENAME, guess, &mut corrector);
-        assert_eq!("jason",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "jason",&suggested_word.as_ref().unwrap()));
+        assert_eq!("Jason",suggested_word.as_ref().unwrap(), "{}", create_error_message(guess, "Jason",&suggested_word.as_ref().unwrap()));
         
         let guess: &str = "is"; 
         let suggested_word = test(BIG_FILENAME, guess, &mut corrector);
@@ -0,0 +1,205 @@
+use super::error::SpellError;
+
+// Layout, all integers little-endian:
+//   magic "SPDT", version u16, word count u32, payload length u64, FNV-1a checksum of payload u64
+//   payload: per word in sorted order, the bytes shared with the previous word, the length of
+//   the rest, the rest as UTF-8 and the frequency, each number as a LEB128 varint
+const MAGIC: &[u8; 4] = b"SPDT";
+const VERSION: u16 = 1;
+const HEADER_LENGTH: usize = 4 + 2 + 4 + 8 + 8;
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// Words must come in sorted order for the shared prefixes to pay off, which Trie::iter gives
+pub fn encode<I: IntoIterator<Item = (String, u32)>>(words: I) -> Vec<u8> {
+    let mut payload = Vec::new();
+    let mut num_words: u32 = 0;
+    let mut prev = String::new();
+
+    for (word, freq) in words {
+        let shared: usize = prev.chars().zip(word.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let rest = &word.as_bytes()[shared..];
+        write_varint(&mut payload, shared as u64);
+        write_varint(&mut payload, rest.len() as u64);
+        payload.extend_from_slice(rest);
+        write_varint(&mut payload, freq as u64);
+
+        num_words += 1;
+        prev = word;
+    }
+
+    let mut out = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&num_words.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&checksum(&payload).to_le_bytes());
+    out.extend_from_slice(&payload);
+    out
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<(String, u32)>, SpellError> {
+    if bytes.len() < HEADER_LENGTH || &bytes[..4] != MAGIC {
+        return Err(SpellError::InvalidDictionary("not a binary dictionary"));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != VERSION {
+        return Err(SpellError::InvalidDictionary("unsupported version"));
+    }
+    let num_words = u32::from_le_bytes(bytes[6..10].try_into().unwrap());
+    let payload_length = u64::from_le_bytes(bytes[10..18].try_into().unwrap());
+    let expected_checksum = u64::from_le_bytes(bytes[18..26].try_into().unwrap());
+
+    let payload = &bytes[HEADER_LENGTH..];
+    if payload.len() as u64 != payload_length {
+        return Err(SpellError::InvalidDictionary("wrong length"));
+    }
+    if checksum(payload) != expected_checksum {
+        return Err(SpellError::InvalidDictionary("checksum mismatch"));
+    }
+
+    // The checksum doesn't cover the word count, and every word takes at least a byte
+    let mut out = Vec::with_capacity((num_words as usize).min(payload.len()));
+    let mut prev: Vec<u8> = Vec::new();
+    let mut pos = 0;
+    for _ in 0..num_words {
+        let shared = read_varint(payload, &mut pos)? as usize;
+        let rest_length = read_varint(payload, &mut pos)? as usize;
+        if shared > prev.len() || rest_length > payload.len() - pos {
+            return Err(SpellError::InvalidDictionary("truncated"));
+        }
+        prev.truncate(shared);
+        prev.extend_from_slice(&payload[pos..pos + rest_length]);
+        pos += rest_length;
+
+        let freq = u32::try_from(read_varint(payload, &mut pos)?)
+            .map_err(|_| SpellError::InvalidDictionary("frequency out of range"))?;
+        let word = String::from_utf8(prev.clone())
+            .map_err(|_| SpellError::InvalidDictionary("invalid UTF-8"))?;
+        out.push((word, freq));
+    }
+    if pos != payload.len() {
+        return Err(SpellError::InvalidDictionary("trailing bytes"));
+    }
+
+    Ok(out)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, SpellError> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or(SpellError::InvalidDictionary("truncated"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(SpellError::InvalidDictionary("number too long"))
+}
+
+// 64-bit FNV-1a, enough to catch truncation and bit rot
+fn checksum(bytes: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET, bytes)
+}
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+// FNV-1a over each word as UTF-8, a 0xff byte that UTF-8 never uses, then its frequency. Unlike
+// Hash it depends on nothing but the words, so it stays the same across builds and runs and can
+// key a cache of anything compiled from them. Words must come in sorted order
+pub fn digest<I: IntoIterator<Item = (String, u32)>>(words: I) -> u64 {
+    words.into_iter().fold(FNV_OFFSET, |hash, (word, freq)| {
+        let hash = fnv1a(hash, word.as_bytes());
+        let hash = fnv1a(hash, &[0xff]);
+        fnv1a(hash, &freq.to_le_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> Vec<(String, u32)> {
+        [("car", 2), ("cares", 1), ("caress", 300), ("café", 1), ("zebra", 70000)]
+            .iter().map(|(word, freq)| (word.to_string(), *freq)).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let words = setup();
+        let bytes = encode(words.clone());
+
+        assert_eq!(b"SPDT", &bytes[..4], "Missing magic number");
+        assert_eq!(words, decode(&bytes).unwrap(), "Words changed in the round trip");
+        assert!(decode(&encode(Vec::new())).unwrap().is_empty(), "Found words in an empty dictionary");
+
+        let text_length: usize = words.iter().map(|(word, _)| word.len() + 1).sum();
+        assert!(bytes.len() - HEADER_LENGTH < text_length + words.len() * 2, "Shared prefixes not compressed");
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value);
+            let mut pos = 0;
+            assert_eq!(value, read_varint(&bytes, &mut pos).unwrap(), "Incorrect varint round trip");
+            assert_eq!(bytes.len(), pos, "Varint not read to its end");
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let words = setup();
+        assert_eq!(digest(words.clone()), digest(words.clone()), "Same words give different digests");
+        assert_eq!(0xa720ba0bd82d1e74, digest(words.clone()), "Digest changed between builds");
+        assert_eq!(FNV_OFFSET, digest(Vec::new()), "Incorrect digest of no words");
+
+        let mut changed = words.clone();
+        changed[0].1 += 1;
+        assert_ne!(digest(words.clone()), digest(changed), "Frequency change not reflected in the digest");
+        let split = vec![("ca".to_string(), 1), ("r".to_string(), 1)];
+        let joined = vec![("car".to_string(), 1)];
+        assert_ne!(digest(split), digest(joined), "Word boundaries not reflected in the digest");
+    }
+
+    #[test]
+    fn test_invalid() {
+        let bytes = encode(setup());
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(decode(&corrupted), Err(SpellError::InvalidDictionary("checksum mismatch"))), "Corrupted payload accepted");
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 99;
+        assert!(matches!(decode(&wrong_version), Err(SpellError::InvalidDictionary("unsupported version"))), "Unknown version accepted");
+
+        assert!(matches!(decode(&bytes[..bytes.len() - 1]), Err(SpellError::InvalidDictionary("wrong length"))), "Truncated file accepted");
+
+        let mut wrong_count = bytes.clone();
+        wrong_count[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(decode(&wrong_count), Err(SpellError::InvalidDictionary("truncated"))), "Corrupted word count accepted");
+        assert!(matches!(decode(b"yea yeah"), Err(SpellError::InvalidDictionary("not a binary dictionary"))), "Text file accepted");
+        assert!(matches!(decode(&[]), Err(SpellError::InvalidDictionary("not a binary dictionary"))), "Empty file accepted");
+    }
+}
@@ -0,0 +1,108 @@
+use super::distance;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+struct BkNode {
+    word: String,
+    children: BTreeMap<u32, Box<BkNode>>,
+}
+
+// Burkhard-Keller tree over Damerau-Levenshtein distance. Each child edge is labelled with
+// its distance from the parent, so the triangle inequality rules out every edge further
+// than max_distance from the query's own distance to the parent
+#[derive(Debug, Clone, Default)]
+pub struct BkTree {
+    root: Option<Box<BkNode>>,
+    num_words: usize,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self {root: None, num_words: 0}
+    }
+
+    pub fn get_word_count(&self) -> usize {
+        self.num_words
+    }
+
+    pub fn add(&mut self, word: &str) {
+        let mut curr_node = match &mut self.root {
+            Some(node) => node,
+            None => {
+                self.root = Some(Box::new(BkNode {word: word.to_string(), children: BTreeMap::new()}));
+                self.num_words += 1;
+                return;
+            }
+        };
+
+        loop {
+            let edge = distance::damerau_levenshtein(word, &curr_node.word);
+            if edge == 0 {
+                return;
+            }
+            match curr_node.children.entry(edge) {
+                Entry::Vacant(entry) => {
+                    entry.insert(Box::new(BkNode {word: word.to_string(), children: BTreeMap::new()}));
+                    self.num_words += 1;
+                    return;
+                }
+                Entry::Occupied(entry) => curr_node = entry.into_mut(),
+            }
+        }
+    }
+
+    pub fn find_within(&self, word: &str, max_distance: u32) -> Vec<(String, u32)> {
+        let mut out = Vec::new();
+        let mut stack: Vec<&BkNode> = self.root.iter().map(|node| &**node).collect();
+
+        while let Some(curr_node) = stack.pop() {
+            let distance = distance::damerau_levenshtein(word, &curr_node.word);
+            if distance <= max_distance {
+                out.push((curr_node.word.clone(), distance));
+            }
+
+            let low = distance.saturating_sub(max_distance);
+            let high = distance + max_distance;
+            for (_, next_node) in curr_node.children.range(low..=high) {
+                stack.push(next_node);
+            }
+        }
+
+        out.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> BkTree {
+        let mut tree = BkTree::new();
+        for word in ["cares", "caress", "baboon", "car", "car", "café"] {
+            tree.add(word);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_add() {
+        let tree = setup();
+        assert_eq!(5, tree.get_word_count(), "Incorrect word count after duplicate add");
+        assert_eq!(0, BkTree::new().get_word_count(), "Incorrect word count on empty tree");
+        assert!(BkTree::new().find_within("car", 3).is_empty(), "Found words in empty tree");
+    }
+
+    #[test]
+    fn test_find_within() {
+        let tree = setup();
+
+        assert_eq!(vec![("car".to_string(), 1), ("cares".to_string(), 1)], tree.find_within("cars", 1), "Incorrect words within distance one of \"cars\"");
+        assert_eq!(vec![("cares".to_string(), 1), ("caress".to_string(), 2)], tree.find_within("acres", 2), "Incorrect words within distance two of \"acres\"");
+        assert_eq!(vec![("café".to_string(), 1), ("car".to_string(), 2), ("cares".to_string(), 2)], tree.find_within("cafe", 2), "Words not ordered by distance, then alphabetically");
+        assert_eq!(vec![("baboon".to_string(), 0)], tree.find_within("baboon", 0), "Exact match not found at distance zero");
+        assert!(tree.find_within("zzzzzz", 3).is_empty(), "Found words for nonsense query");
+    }
+}
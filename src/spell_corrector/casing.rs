@@ -0,0 +1,91 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    Lower,
+    Title,
+    Upper,
+    // Anything else, like "iPhone", which only a dictionary entry can reproduce
+    Mixed,
+}
+
+// A single capital letter reads as Title, since "I" and "A" start sentences far more often
+// than they shout
+pub fn detect(word: &str) -> Casing {
+    let cased: Vec<char> = word.chars().filter(|letter| letter.is_uppercase() || letter.is_lowercase()).collect();
+    let num_upper = cased.iter().filter(|letter| letter.is_uppercase()).count();
+
+    if num_upper == 0 {
+        Casing::Lower
+    }
+    else if num_upper == cased.len() && num_upper > 1 {
+        Casing::Upper
+    }
+    else if num_upper == 1 && cased[0].is_uppercase() {
+        Casing::Title
+    }
+    else {
+        Casing::Mixed
+    }
+}
+
+pub fn apply(casing: Casing, word: &str) -> String {
+    match casing {
+        Casing::Lower | Casing::Mixed => word.to_string(),
+        Casing::Upper => word.to_uppercase(),
+        Casing::Title => {
+            let mut letters = word.chars();
+            match letters.next() {
+                Some(first) => first.to_uppercase().chain(letters).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+// Whether the word at `start` opens a sentence, where a capital says nothing about the word.
+// The start of the text doesn't count, so the first entry of a word list keeps its capitals
+pub fn follows_sentence_end(text: &str, start: usize) -> bool {
+    let before = text[..start].trim_end_matches(|letter: char| letter.is_whitespace() || "\"'“‘(«".contains(letter));
+    before.ends_with(['.', '!', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Casing::Lower, detect("jason"), "Incorrect casing for lowercase word");
+        assert_eq!(Casing::Title, detect("Jason"), "Incorrect casing for title case word");
+        assert_eq!(Casing::Upper, detect("NASA"), "Incorrect casing for uppercase word");
+        assert_eq!(Casing::Mixed, detect("iPhone"), "Incorrect casing for mixed case word");
+        assert_eq!(Casing::Mixed, detect("McDonald"), "Incorrect casing for mixed case word");
+        assert_eq!(Casing::Title, detect("I"), "Single capital not read as title case");
+        assert_eq!(Casing::Title, detect("Don't"), "Apostrophe affected casing");
+        assert_eq!(Casing::Upper, detect("ÉCOLE"), "Accented capital not detected");
+        assert_eq!(Casing::Lower, detect(""), "Incorrect casing for empty word");
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!("jason", apply(Casing::Lower, "jason"), "Lowercase changed the word");
+        assert_eq!("Jason", apply(Casing::Title, "jason"), "Title case not applied");
+        assert_eq!("NASA", apply(Casing::Upper, "nasa"), "Uppercase not applied");
+        assert_eq!("iphone", apply(Casing::Mixed, "iphone"), "Mixed case changed the word");
+        assert_eq!("Élan", apply(Casing::Title, "élan"), "Accented title case not applied");
+        assert_eq!("STRASSE", apply(Casing::Upper, "straße"), "Uppercase did not follow Unicode rules");
+        assert_eq!("", apply(Casing::Title, ""), "Title case of empty word not empty");
+    }
+
+    #[test]
+    fn test_follows_sentence_end() {
+        let text = "Jason ran. Then \"Fred\" did. \"Bob\" did! Why? NASA, Ok";
+        assert!(!follows_sentence_end(text, 0), "Start of text treated as a sentence start");
+        assert!(follows_sentence_end(text, text.find("Then").unwrap()), "Word after a full stop not a sentence start");
+        assert!(!follows_sentence_end(text, text.find("Fred").unwrap()), "Quoted word mid-sentence treated as a sentence start");
+        assert!(follows_sentence_end(text, text.find("Bob").unwrap()), "Quoted word after a full stop not a sentence start");
+        assert!(follows_sentence_end(text, text.find("Why").unwrap()), "Word after an exclamation mark not a sentence start");
+        assert!(follows_sentence_end(text, text.find("NASA").unwrap()), "Word after a question mark not a sentence start");
+        assert!(!follows_sentence_end(text, text.find("Ok").unwrap()), "Word after a comma treated as a sentence start");
+    }
+}
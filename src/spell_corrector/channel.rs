@@ -0,0 +1,203 @@
+use super::error::SpellError;
+use std::cmp::min;
+use std::collections::HashMap;
+use std::fs;
+
+// Stands in for the letter before the first one, so edits at the start of a word have a context
+const START: char = '^';
+
+// One step from the intended word to the typed one. Letters are given as the writer meant
+// them, except for the typed letters of insertions and substitutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edit {
+    // (previous, dropped): the letter after `previous` was left out
+    Deletion(char, char),
+    // (previous, added): an extra letter was typed after `previous`
+    Insertion(char, char),
+    // (typed, intended)
+    Substitution(char, char),
+    // (first, second): the pair was typed in the wrong order
+    Transposition(char, char),
+}
+
+// Aligns the typed word against the intended one with single adjacent transpositions, which is
+// all the confusion matrices can describe, and lists the edits in word order
+pub fn edits(typo: &str, word: &str) -> Vec<Edit> {
+    let typed: Vec<char> = typo.chars().collect();
+    let intended: Vec<char> = word.chars().collect();
+
+    let mut cells = vec![vec![0; intended.len() + 1]; typed.len() + 1];
+    for (i, row) in cells.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in cells[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=typed.len() {
+        for j in 1..=intended.len() {
+            let cost = if typed[i - 1] == intended[j - 1] {0} else {1};
+            cells[i][j] = min(min(cells[i - 1][j] + 1, cells[i][j - 1] + 1), cells[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && typed[i - 1] == intended[j - 2] && typed[i - 2] == intended[j - 1] {
+                cells[i][j] = min(cells[i][j], cells[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    let previous = |j: usize| if j > 0 {intended[j - 1]} else {START};
+    let mut out = Vec::new();
+    let (mut i, mut j) = (typed.len(), intended.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && typed[i - 1] == intended[j - 1] && cells[i][j] == cells[i - 1][j - 1] {
+            i -= 1;
+            j -= 1;
+        }
+        else if i > 1 && j > 1 && typed[i - 1] == intended[j - 2] && typed[i - 2] == intended[j - 1] && cells[i][j] == cells[i - 2][j - 2] + 1 {
+            out.push(Edit::Transposition(intended[j - 2], intended[j - 1]));
+            i -= 2;
+            j -= 2;
+        }
+        else if i > 0 && j > 0 && cells[i][j] == cells[i - 1][j - 1] + 1 {
+            out.push(Edit::Substitution(typed[i - 1], intended[j - 1]));
+            i -= 1;
+            j -= 1;
+        }
+        else if j > 0 && cells[i][j] == cells[i][j - 1] + 1 {
+            out.push(Edit::Deletion(previous(j - 1), intended[j - 1]));
+            j -= 1;
+        }
+        else {
+            out.push(Edit::Insertion(previous(j), typed[i - 1]));
+            i -= 1;
+        }
+    }
+
+    out.reverse();
+    out
+}
+
+// Kernighan, Church and Gale's noisy channel: each confusion matrix counts how often an edit
+// was made, and is divided by how often the writer had the chance to make it
+#[derive(Debug, Clone, Default)]
+pub struct ErrorModel {
+    edit_counts: HashMap<Edit, u32>,
+    letter_counts: HashMap<char, u32>,
+    pair_counts: HashMap<(char, char), u32>,
+    num_pairs: usize,
+}
+
+impl ErrorModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Each line holds a misspelling followed by its correction
+    pub fn from_file(file_name: &str) -> Result<Self, SpellError> {
+        let file = fs::read_to_string(file_name)?;
+        let mut model = Self::new();
+
+        for (line_number, line) in file.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => continue,
+                [typo, word] => model.train(&typo.to_lowercase(), &word.to_lowercase()),
+                _ => return Err(SpellError::MalformedLine(line_number + 1)),
+            }
+        }
+
+        Ok(model)
+    }
+
+    pub fn get_pair_count(&self) -> usize {
+        self.num_pairs
+    }
+
+    pub fn get_count(&self, edit: Edit) -> u32 {
+        *self.edit_counts.get(&edit).unwrap_or(&0)
+    }
+
+    pub fn train(&mut self, typo: &str, word: &str) {
+        let mut previous = START;
+        *self.letter_counts.entry(START).or_default() += 1;
+        for letter in word.chars() {
+            *self.letter_counts.entry(letter).or_default() += 1;
+            *self.pair_counts.entry((previous, letter)).or_default() += 1;
+            previous = letter;
+        }
+
+        for edit in edits(typo, word) {
+            *self.edit_counts.entry(edit).or_default() += 1;
+        }
+        self.num_pairs += 1;
+    }
+
+    // Add-one smoothed, so edits never seen in training are unlikely rather than impossible
+    pub fn edit_probability(&self, edit: Edit) -> f64 {
+        let chances = match edit {
+            Edit::Deletion(previous, letter) => self.pair_counts.get(&(previous, letter)),
+            Edit::Insertion(previous, _) => self.letter_counts.get(&previous),
+            Edit::Substitution(_, intended) => self.letter_counts.get(&intended),
+            Edit::Transposition(first, second) => self.pair_counts.get(&(first, second)),
+        };
+        let letters = self.letter_counts.len().max(1) as f64;
+        let probability = (self.get_count(edit) as f64 + 1.0) / (*chances.unwrap_or(&0) as f64 + letters);
+        probability.min(1.0)
+    }
+
+    // P(typo|word), taking the edits as independent
+    pub fn channel_probability(&self, typo: &str, word: &str) -> f64 {
+        edits(typo, word).into_iter().map(|edit| self.edit_probability(edit)).product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> ErrorModel {
+        let mut model = ErrorModel::new();
+        for (typo, word) in [("xar", "car"), ("xap", "cap"), ("xut", "cut"), ("ct", "cat"), ("teh", "the")] {
+            model.train(typo, word);
+        }
+        model
+    }
+
+    #[test]
+    fn test_edits() {
+        assert!(edits("yea", "yea").is_empty(), "Found edits between identical words");
+        assert_eq!(vec![Edit::Deletion('y', 'e')], edits("ya", "yea"), "Incorrect deletion");
+        assert_eq!(vec![Edit::Deletion(START, 'y')], edits("ea", "yea"), "Deletion of first letter not given the start context");
+        assert_eq!(vec![Edit::Insertion('y', 'b')], edits("ybea", "yea"), "Incorrect insertion");
+        assert_eq!(vec![Edit::Insertion(START, 'r')], edits("ryea", "yea"), "Insertion before first letter not given the start context");
+        assert_eq!(vec![Edit::Substitution('b', 'a')], edits("flobt", "float"), "Incorrect substitution");
+        assert_eq!(vec![Edit::Transposition('e', 'a')], edits("yae", "yea"), "Incorrect transposition");
+        assert_eq!(vec![Edit::Substitution('k', 'c'), Edit::Deletion('f', 'é')], edits("kaf", "café"), "Edits not listed in word order");
+    }
+
+    #[test]
+    fn test_train() {
+        let model = setup();
+
+        assert_eq!(5, model.get_pair_count(), "Incorrect pair count");
+        assert_eq!(3, model.get_count(Edit::Substitution('x', 'c')), "Substitutions not counted");
+        assert_eq!(1, model.get_count(Edit::Deletion('c', 'a')), "Deletions not counted");
+        assert_eq!(1, model.get_count(Edit::Transposition('h', 'e')), "Transpositions not counted");
+        assert_eq!(0, model.get_count(Edit::Insertion('c', 'a')), "Counted an edit never made");
+        assert_eq!(0, ErrorModel::new().get_pair_count(), "Incorrect pair count on empty model");
+    }
+
+    #[test]
+    fn test_channel_probability() {
+        let model = setup();
+
+        assert_eq!(1.0, model.channel_probability("cat", "cat"), "Unedited word not certain");
+        assert!(model.channel_probability("xat", "cat") > model.channel_probability("xat", "bat"), "Frequent substitution not more likely than an unseen one");
+        assert!(model.channel_probability("xat", "cat") > model.channel_probability("xxat", "cat"), "Extra edit did not lower the probability");
+        assert!(model.channel_probability("zzz", "cat") > 0.0, "Unseen edits ruled out");
+        assert!(ErrorModel::new().channel_probability("xat", "cat") <= 1.0, "Untrained model gives probability above one");
+
+        // Three of the four c's were typed as x, smoothed over the start marker and eight letters
+        let substitution = model.edit_probability(Edit::Substitution('x', 'c'));
+        assert_eq!(4.0 / (4.0 + 9.0), substitution, "Incorrect smoothed substitution probability");
+    }
+}
@@ -0,0 +1,111 @@
+use super::error::SpellError;
+use super::tokenizer;
+use std::collections::HashMap;
+use std::fs;
+
+// Fills in the context before the first word of a sentence
+pub const START: &str = "<s>";
+
+// Brants et al.'s stupid backoff: an unseen n-gram falls back to the shorter one at this discount
+const BACKOFF: f64 = 0.4;
+
+#[derive(Debug, Clone, Default)]
+pub struct ContextModel {
+    unigrams: HashMap<String, u32>,
+    bigrams: HashMap<(String, String), u32>,
+    trigrams: HashMap<(String, String, String), u32>,
+    bigram_contexts: HashMap<String, u32>,
+    trigram_contexts: HashMap<(String, String), u32>,
+    num_words: u64,
+}
+
+impl ContextModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reads the same kind of file as use_dictionary, taking each line as a sentence
+    pub fn from_file(file_name: &str) -> Result<Self, SpellError> {
+        let file = fs::read_to_string(file_name)?;
+        let mut model = Self::new();
+
+        for line in file.lines() {
+            let words: Vec<String> = tokenizer::tokenize(line).iter().map(|token| token.get_word().to_lowercase()).collect();
+            model.add_sentence(&words);
+        }
+
+        Ok(model)
+    }
+
+    pub fn get_word_count(&self) -> u64 {
+        self.num_words
+    }
+
+    pub fn add_sentence(&mut self, words: &[String]) {
+        let (mut prev2, mut prev1) = (START.to_string(), START.to_string());
+        for word in words {
+            *self.unigrams.entry(word.clone()).or_default() += 1;
+            *self.bigrams.entry((prev1.clone(), word.clone())).or_default() += 1;
+            *self.bigram_contexts.entry(prev1.clone()).or_default() += 1;
+            *self.trigrams.entry((prev2.clone(), prev1.clone(), word.clone())).or_default() += 1;
+            *self.trigram_contexts.entry((prev2, prev1.clone())).or_default() += 1;
+            self.num_words += 1;
+
+            prev2 = prev1;
+            prev1 = word.clone();
+        }
+    }
+
+    // Relative score of `word` following `prev2 prev1`. Stupid backoff scores are not normalised,
+    // but they only ever get compared with each other
+    pub fn probability(&self, prev2: &str, prev1: &str, word: &str) -> f64 {
+        let trigram = (prev2.to_string(), prev1.to_string(), word.to_string());
+        if let Some(count) = self.trigrams.get(&trigram) {
+            return *count as f64 / self.trigram_contexts[&(trigram.0, trigram.1)] as f64;
+        }
+
+        let bigram = (trigram.1, trigram.2);
+        if let Some(count) = self.bigrams.get(&bigram) {
+            return BACKOFF * *count as f64 / self.bigram_contexts[&bigram.0] as f64;
+        }
+
+        // Add-one smoothed, so words missing from the corpus are unlikely rather than impossible
+        let count = *self.unigrams.get(word).unwrap_or(&0) as f64;
+        BACKOFF * BACKOFF * (count + 1.0) / (self.num_words as f64 + self.unigrams.len() as f64 + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> ContextModel {
+        let mut model = ContextModel::new();
+        for sentence in ["there is a cat", "there is a dog", "their cat is here"] {
+            let words: Vec<String> = sentence.split(' ').map(|word| word.to_string()).collect();
+            model.add_sentence(&words);
+        }
+        model
+    }
+
+    #[test]
+    fn test_add_sentence() {
+        let model = setup();
+        assert_eq!(12, model.get_word_count(), "Incorrect word count");
+        assert_eq!(0, ContextModel::new().get_word_count(), "Incorrect word count on empty model");
+    }
+
+    #[test]
+    fn test_probability() {
+        let model = setup();
+
+        assert_eq!(2.0 / 3.0, model.probability(START, START, "there"), "Incorrect trigram probability at sentence start");
+        assert_eq!(1.0, model.probability("there", "is", "a"), "Incorrect trigram probability");
+        assert_eq!(BACKOFF * 1.0 / 3.0, model.probability("their", "is", "here"), "Did not back off to the bigram");
+        assert_eq!(BACKOFF * BACKOFF * 4.0 / 20.0, model.probability(START, "their", "is"), "Did not back off to the unigram");
+        assert!(model.probability(START, START, "there") > model.probability(START, START, "their"), "Frequent sentence start not more likely");
+        assert!(model.probability("there", "is", "zebra") > 0.0, "Unseen word ruled out");
+        assert!(model.probability("there", "is", "zebra") < model.probability("there", "is", "cat"), "Unseen word as likely as a seen one");
+    }
+}
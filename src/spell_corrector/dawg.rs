@@ -0,0 +1,242 @@
+use super::pattern::Pattern;
+use super::trie::{Node, Trie, TrieCursor, Words};
+use std::collections::HashMap;
+use std::slice;
+
+// States already built, by whether they end a word and their edges
+type Registry = HashMap<(bool, Vec<(char, u32)>), u32>;
+
+#[derive(Debug, Clone)]
+struct State {
+    is_final: bool,
+    // Words at or below this state
+    count: u32,
+    edges_start: u32,
+    num_edges: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    letter: char,
+    target: u32,
+    // Words below the source state that sort before those below the target
+    offset: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionStats {
+    nodes_before: u32,
+    nodes_after: u32,
+    edges_after: u32,
+}
+
+impl CompressionStats {
+    pub fn get_nodes_before(&self) -> u32 {
+        self.nodes_before
+    }
+    pub fn get_nodes_after(&self) -> u32 {
+        self.nodes_after
+    }
+    pub fn get_edges_after(&self) -> u32 {
+        self.edges_after
+    }
+}
+
+// A trie with every repeated subtree stored once, so common endings like "-ing" and "-ness" are
+// shared. Shared states can't hold a frequency, so words are numbered in lexicographic order by
+// counting the words each state leads to, and the frequencies are kept in that order
+#[derive(Debug, Clone)]
+pub struct Dawg {
+    states: Vec<State>,
+    edges: Vec<Edge>,
+    freqs: Vec<u32>,
+    root: u32,
+    trie_node_count: u32,
+}
+
+impl Dawg {
+    pub fn from_trie(trie: &Trie) -> Self {
+        let mut dawg = Self {states: Vec::new(), edges: Vec::new(), freqs: trie.iter().map(|(_, freq)| freq).collect(), root: 0, trie_node_count: trie.get_node_count()};
+        let mut registry = HashMap::new();
+        dawg.root = dawg.register(trie.get_root(), &mut registry);
+        dawg
+    }
+
+    // Minimizes bottom up: once its children are shared, a node is the same as any other that
+    // ends a word the same way and has the same edges
+    fn register(&mut self, curr_node: &Node, registry: &mut Registry) -> u32 {
+        let children: Vec<(char, u32)> = curr_node.child_nodes()
+            .map(|next_node| (next_node.get_value(), self.register(next_node, registry)))
+            .collect();
+        let key = (curr_node.get_freq() > 0, children);
+        if let Some(state) = registry.get(&key) {
+            return *state;
+        }
+
+        let edges_start = self.edges.len() as u32;
+        let mut count = key.0 as u32;
+        for (letter, target) in key.1.iter() {
+            self.edges.push(Edge {letter: *letter, target: *target, offset: count});
+            count += self.states[*target as usize].count;
+        }
+        self.states.push(State {is_final: key.0, count, edges_start, num_edges: key.1.len() as u32});
+
+        let state = self.states.len() as u32 - 1;
+        registry.insert(key, state);
+        state
+    }
+
+    pub fn get_root(&self) -> DawgNode<'_> {
+        DawgNode {dawg: self, state: self.root, letter: '\0', rank: 0}
+    }
+    pub fn get_word_count(&self) -> u32 {
+        self.freqs.len() as u32
+    }
+    pub fn get_node_count(&self) -> u32 {
+        self.states.len() as u32
+    }
+    pub fn get_stats(&self) -> CompressionStats {
+        CompressionStats {nodes_before: self.trie_node_count, nodes_after: self.get_node_count(), edges_after: self.edges.len() as u32}
+    }
+
+    pub fn find(&self, word: &String) -> Option<DawgNode<'_>> {
+        self.get_root().descend(&word.to_lowercase()).filter(|node| node.get_freq() >= 1)
+    }
+
+    pub fn fuzzy_find(&self, word: &String, max_distance: u32) -> Vec<(String, u32)> {
+        self.get_root().fuzzy_find(&word.to_lowercase(), max_distance)
+    }
+
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<(String, u32)> {
+        self.get_root().find_pattern(pattern)
+    }
+
+    pub fn anagrams(&self, letters: &str, partial: bool) -> Vec<(String, u32)> {
+        self.get_root().anagrams(letters, partial)
+    }
+
+    // Shared states have no single highest frequency below them, so completion can't go
+    // best-first and sorts everything under the prefix instead
+    pub fn complete(&self, prefix: &String, k: usize) -> Vec<(String, u32)> {
+        let lower_prefix = prefix.to_lowercase();
+        match self.get_root().descend(&lower_prefix) {
+            Some(curr_node) => curr_node.complete(lower_prefix, k, false),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn iter(&self) -> Words<DawgNode<'_>> {
+        self.get_root().words(String::new())
+    }
+
+    fn get_edges(&self, state: u32) -> &[Edge] {
+        let state = &self.states[state as usize];
+        &self.edges[state.edges_start as usize..(state.edges_start + state.num_edges) as usize]
+    }
+}
+
+// A state reached along a particular path, which fixes the letter leading to it and the number
+// of its first word
+#[derive(Debug, Clone, Copy)]
+pub struct DawgNode<'a> {
+    dawg: &'a Dawg,
+    state: u32,
+    letter: char,
+    rank: u32,
+}
+
+pub struct DawgChildren<'a> {
+    dawg: &'a Dawg,
+    edges: slice::Iter<'a, Edge>,
+    rank: u32,
+}
+
+impl<'a> Iterator for DawgChildren<'a> {
+    type Item = DawgNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.edges.next().map(|edge| DawgNode {dawg: self.dawg, state: edge.target, letter: edge.letter, rank: self.rank + edge.offset})
+    }
+}
+
+impl<'a> TrieCursor for DawgNode<'a> {
+    type Children = DawgChildren<'a>;
+
+    fn get_value(&self) -> char {
+        self.letter
+    }
+    fn get_freq(&self) -> u32 {
+        match self.dawg.states[self.state as usize].is_final {
+            true => self.dawg.freqs[self.rank as usize],
+            false => 0,
+        }
+    }
+    // Scans the frequencies of every word below, since no shared state can keep it
+    fn get_max_freq(&self) -> u32 {
+        let count = self.dawg.states[self.state as usize].count;
+        self.dawg.freqs[self.rank as usize..(self.rank + count) as usize].iter().copied().max().unwrap_or(0)
+    }
+    fn get_child(&self, letter: char) -> Option<Self> {
+        let edges = self.dawg.get_edges(self.state);
+        let index = edges.binary_search_by(|edge| edge.letter.cmp(&letter)).ok()?;
+        let edge = edges[index];
+        Some(DawgNode {dawg: self.dawg, state: edge.target, letter, rank: self.rank + edge.offset})
+    }
+    fn child_nodes(&self) -> Self::Children {
+        DawgChildren {dawg: self.dawg, edges: self.dawg.get_edges(self.state).iter(), rank: self.rank}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> (Trie, Dawg) {
+        let mut trie = Trie::new();
+        for (word, freq) in [("tap", 3), ("taps", 1), ("top", 5), ("tops", 2), ("walking", 4), ("talking", 1), ("talk", 7), ("walk", 2), ("café", 1)] {
+            trie.set_freq(&word.to_string(), freq);
+        }
+        let dawg = Dawg::from_trie(&trie);
+        (trie, dawg)
+    }
+
+    #[test]
+    fn test_minimize() {
+        let (trie, dawg) = setup();
+        let stats = dawg.get_stats();
+
+        assert_eq!(trie.get_node_count(), stats.get_nodes_before(), "Incorrect node count before minimizing");
+        assert_eq!(dawg.get_node_count(), stats.get_nodes_after(), "Incorrect node count after minimizing");
+        assert_eq!(24, stats.get_nodes_before(), "Incorrect trie node count");
+        assert_eq!(15, stats.get_nodes_after(), "Shared endings not merged");
+        assert_eq!(trie.get_word_count(), dawg.get_word_count(), "Incorrect word count");
+
+        let single = Dawg::from_trie(&["a"].into_iter().collect());
+        assert_eq!(2, single.get_node_count(), "Incorrect node count for a single letter");
+        assert_eq!(0, Dawg::from_trie(&Trie::new()).get_word_count(), "Found words in an empty dictionary");
+    }
+
+    #[test]
+    fn test_same_as_trie() {
+        let (trie, dawg) = setup();
+
+        assert_eq!(trie.iter().collect::<Vec<_>>(), dawg.iter().collect::<Vec<_>>(), "Words or frequencies differ from the trie");
+        for (word, _) in trie.iter() {
+            assert_eq!(trie.find(&word).map(|node| node.get_freq()), dawg.find(&word).map(|node| node.get_freq()), "Incorrect frequency for \"{}\"", word);
+        }
+        assert!(dawg.find(&"tal".to_string()).is_none(), "Found prefix that isn't a word");
+        assert!(dawg.find(&"walks".to_string()).is_none(), "Found a word made up from shared endings");
+        for word in ["tapz", "wakling", "cafe", "x"] {
+            let mut expected = trie.fuzzy_find(&word.to_string(), 2);
+            let mut actual = dawg.fuzzy_find(&word.to_string(), 2);
+            expected.sort();
+            actual.sort();
+            assert_eq!(expected, actual, "Fuzzy search differs from the trie for \"{}\"", word);
+        }
+        for prefix in ["t", "ta", "walk", "", "q"] {
+            assert_eq!(trie.complete(&prefix.to_string(), 3), dawg.complete(&prefix.to_string(), 3), "Completions differ from the trie for \"{}\"", prefix);
+        }
+        assert_eq!(7, dawg.get_root().descend("t").unwrap().get_max_freq(), "Incorrect highest frequency below a state");
+    }
+}
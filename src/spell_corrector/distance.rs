@@ -0,0 +1,135 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
+// Damerau-Levenshtein distance: insertions, deletions, substitutions and swaps of
+// adjacent letters all cost one, and letters may be edited again after a swap
+pub fn damerau_levenshtein(word1: &str, word2: &str) -> u32 {
+    let letters1: Vec<char> = word1.chars().collect();
+    let letters2: Vec<char> = word2.chars().collect();
+
+    let mut rows: Vec<Vec<u32>> = vec![(0..=letters2.len() as u32).collect()];
+    let mut last_rows: HashMap<char, usize> = HashMap::new();
+
+    for letter in letters1 {
+        let row = next_row(&rows, &last_rows, letter, &letters2);
+        rows.push(row);
+        last_rows.insert(letter, rows.len() - 1);
+    }
+
+    rows[rows.len() - 1][letters2.len()]
+}
+
+// Computes the row for appending `letter` to the word whose rows are already in `rows`.
+// `last_rows` maps each letter to the last row that appended it, so a row can be computed
+// while walking a trie as long as the caller keeps one row per level of the current path
+pub fn next_row(rows: &[Vec<u32>], last_rows: &HashMap<char, usize>, letter: char, letters: &[char]) -> Vec<u32> {
+    let i = rows.len();
+    let prev_row = &rows[i - 1];
+    let mut row = vec![i as u32; letters.len() + 1];
+    let mut last_col = 0;
+
+    for j in 1..=letters.len() {
+        let last_row = *last_rows.get(&letters[j - 1]).unwrap_or(&0);
+        let prev_col = last_col;
+        let cost = if letters[j - 1] == letter {
+            last_col = j;
+            0
+        }
+        else {
+            1
+        };
+        row[j] = min(min(row[j - 1] + 1, prev_row[j] + 1), prev_row[j - 1] + cost);
+
+        if last_row > 0 && prev_col > 0 {
+            let transposition = rows[last_row - 1][prev_col - 1] + (i - last_row - 1) as u32 + 1 + (j - prev_col - 1) as u32;
+            row[j] = min(row[j], transposition);
+        }
+    }
+
+    row
+}
+
+// Same alignment as damerau_levenshtein, but substitutions are priced by the caller so
+// that near misses can count for less than a whole edit
+pub fn weighted_damerau_levenshtein<F>(word1: &str, word2: &str, substitution_cost: F) -> f64
+where
+    F: Fn(char, char) -> f64,
+{
+    let letters1: Vec<char> = word1.chars().collect();
+    let letters2: Vec<char> = word2.chars().collect();
+    let unreachable = (letters1.len() + letters2.len()) as f64;
+
+    // Row and column zero are sentinels, so cell (i + 1, j + 1) holds prefixes of length i and j
+    let mut cells = vec![vec![0.0; letters2.len() + 2]; letters1.len() + 2];
+    cells[0][0] = unreachable;
+    for i in 0..=letters1.len() {
+        cells[i + 1][0] = unreachable;
+        cells[i + 1][1] = i as f64;
+    }
+    for j in 0..=letters2.len() {
+        cells[0][j + 1] = unreachable;
+        cells[1][j + 1] = j as f64;
+    }
+
+    let mut last_rows: HashMap<char, usize> = HashMap::new();
+    for i in 1..=letters1.len() {
+        let mut last_col = 0;
+        for j in 1..=letters2.len() {
+            let last_row = *last_rows.get(&letters2[j - 1]).unwrap_or(&0);
+            let prev_col = last_col;
+            let cost = if letters1[i - 1] == letters2[j - 1] {
+                last_col = j;
+                0.0
+            }
+            else {
+                substitution_cost(letters1[i - 1], letters2[j - 1])
+            };
+
+            let transposition = cells[last_row][prev_col] + (i - last_row - 1) as f64 + 1.0 + (j - prev_col - 1) as f64;
+            cells[i + 1][j + 1] = (cells[i][j] + cost)
+                .min(cells[i + 1][j] + 1.0)
+                .min(cells[i][j + 1] + 1.0)
+                .min(transposition);
+        }
+        last_rows.insert(letters1[i - 1], i);
+    }
+
+    cells[letters1.len() + 1][letters2.len() + 1]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_damerau_levenshtein() {
+        assert_eq!(0, damerau_levenshtein("yea", "yea"), "Identical words have non-zero distance");
+        assert_eq!(3, damerau_levenshtein("", "yea"), "Distance from empty word is not its length");
+        assert_eq!(3, damerau_levenshtein("yea", ""), "Distance to empty word is not its length");
+        assert_eq!(1, damerau_levenshtein("ye", "yea"), "Incorrect insertion distance");
+        assert_eq!(1, damerau_levenshtein("ybea", "yea"), "Incorrect deletion distance");
+        assert_eq!(1, damerau_levenshtein("flobt", "float"), "Incorrect substitution distance");
+        assert_eq!(1, damerau_levenshtein("yaeh", "yeah"), "Incorrect transposition distance");
+        assert_eq!(2, damerau_levenshtein("eay", "yea"), "Incorrect distance for two edits");
+        assert_eq!(2, damerau_levenshtein("ca", "abc"), "Letters not editable after a transposition");
+        assert_eq!(2, damerau_levenshtein("yade", "yea"), "Letters not editable after a transposition");
+        assert_eq!(1, damerau_levenshtein("cafe", "café"), "Accented letter not treated as a single character");
+        assert_eq!(damerau_levenshtein("absolustly", "absolutely"), damerau_levenshtein("absolutely", "absolustly"), "Distance is not symmetric");
+    }
+
+    #[test]
+    fn test_weighted_damerau_levenshtein() {
+        let unweighted = |_: char, _: char| 1.0;
+        for (word1, word2) in [("yea", "yea"), ("", "yea"), ("flobt", "float"), ("yaeh", "yeah"), ("ca", "abc"), ("yade", "yea"), ("absolustly", "absolutely")] {
+            assert_eq!(damerau_levenshtein(word1, word2) as f64, weighted_damerau_levenshtein(word1, word2, unweighted),
+                       "Unit weights disagree with unweighted distance for \"{}\" and \"{}\"", word1, word2);
+        }
+
+        let half_vowels = |letter1: char, letter2: char| if "aeiou".contains(letter1) && "aeiou".contains(letter2) {0.5} else {1.0};
+        assert_eq!(0.5, weighted_damerau_levenshtein("flout", "float", half_vowels), "Discounted substitution not applied");
+        assert_eq!(1.5, weighted_damerau_levenshtein("bliat", "float", half_vowels), "Discounted and full substitutions not summed");
+        assert_eq!(1.0, weighted_damerau_levenshtein("flaot", "float", half_vowels), "Transposition not priced as one edit");
+        assert_eq!(1.0, weighted_damerau_levenshtein("flat", "float", half_vowels), "Insertion not priced as one edit");
+    }
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum SpellError {
+    Io(io::Error),
+    // Saving rather than reading failed
+    Write(io::Error),
+    UnsupportedCharacter(char),
+    EmptyInput,
+    NoCandidate(String),
+    MalformedLine(usize),
+    InvalidDictionary(&'static str),
+    InvalidPattern(&'static str),
+}
+
+impl fmt::Display for SpellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpellError::Io(error) => write!(f, "Unable to read file: {}", error),
+            SpellError::Write(error) => write!(f, "Unable to save dictionary: {}", error),
+            SpellError::UnsupportedCharacter(letter) => write!(f, "Unsupported character {:?}", letter),
+            SpellError::EmptyInput => write!(f, "No word to correct"),
+            SpellError::NoCandidate(word) => write!(f, "Unable to find word \"{}\"", word),
+            SpellError::MalformedLine(line) => write!(f, "Expected a misspelling and its correction on line {}", line),
+            SpellError::InvalidDictionary(reason) => write!(f, "Invalid binary dictionary: {}", reason),
+            SpellError::InvalidPattern(reason) => write!(f, "Invalid pattern: {}", reason),
+        }
+    }
+}
+
+impl Error for SpellError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpellError::Io(error) | SpellError::Write(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpellError {
+    fn from(error: io::Error) -> Self {
+        SpellError::Io(error)
+    }
+}
@@ -0,0 +1,56 @@
+use super::dawg::Dawg;
+use super::mapped::MappedTrie;
+use super::pattern::Pattern;
+use super::trie::TrieCursor;
+
+// A dictionary that can't take new words, either mapped from a file or minimized in memory.
+// SpellCorrector keeps a regular trie next to it for any words added later
+#[derive(Debug, Clone)]
+pub enum FrozenDictionary {
+    Mapped(MappedTrie),
+    Minimized(Dawg),
+}
+
+impl FrozenDictionary {
+    pub fn get_freq(&self, lower_word: &String) -> Option<u32> {
+        match self {
+            FrozenDictionary::Mapped(trie) => trie.find(lower_word).map(|node| node.get_freq()),
+            FrozenDictionary::Minimized(dawg) => dawg.find(lower_word).map(|node| node.get_freq()),
+        }
+    }
+
+    pub fn fuzzy_find(&self, lower_word: &String, max_distance: u32) -> Vec<(String, u32)> {
+        match self {
+            FrozenDictionary::Mapped(trie) => trie.fuzzy_find(lower_word, max_distance),
+            FrozenDictionary::Minimized(dawg) => dawg.fuzzy_find(lower_word, max_distance),
+        }
+    }
+
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<(String, u32)> {
+        match self {
+            FrozenDictionary::Mapped(trie) => trie.find_pattern(pattern),
+            FrozenDictionary::Minimized(dawg) => dawg.find_pattern(pattern),
+        }
+    }
+
+    pub fn anagrams(&self, lower_letters: &str, partial: bool) -> Vec<(String, u32)> {
+        match self {
+            FrozenDictionary::Mapped(trie) => trie.anagrams(lower_letters, partial),
+            FrozenDictionary::Minimized(dawg) => dawg.anagrams(lower_letters, partial),
+        }
+    }
+
+    pub fn complete(&self, lower_prefix: &String, k: usize) -> Vec<(String, u32)> {
+        match self {
+            FrozenDictionary::Mapped(trie) => trie.complete(lower_prefix, k),
+            FrozenDictionary::Minimized(dawg) => dawg.complete(lower_prefix, k),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (String, u32)> + '_> {
+        match self {
+            FrozenDictionary::Mapped(trie) => Box::new(trie.iter()),
+            FrozenDictionary::Minimized(dawg) => Box::new(dawg.iter()),
+        }
+    }
+}
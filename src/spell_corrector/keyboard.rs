@@ -0,0 +1,101 @@
+pub const ADJACENT_KEY_COST: f64 = 0.5;
+
+// How far each row is shifted right of the one above it, in key widths
+const ROW_OFFSETS: [f64; 3] = [0.0, 0.25, 0.75];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+}
+
+impl KeyboardLayout {
+    fn rows(&self) -> [&'static str; 3] {
+        match self {
+            KeyboardLayout::Qwerty => ["qwertyuiop", "asdfghjkl;", "zxcvbnm,."],
+            KeyboardLayout::Azerty => ["azertyuiop", "qsdfghjklm", "wxcvbn,;:"],
+            KeyboardLayout::Qwertz => ["qwertzuiopü", "asdfghjklöä", "yxcvbnm,."],
+            KeyboardLayout::Dvorak => ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"],
+        }
+    }
+
+    fn position(&self, letter: char) -> Option<(usize, f64)> {
+        for (row, keys) in self.rows().iter().enumerate() {
+            if let Some(col) = keys.chars().position(|key| key == letter) {
+                return Some((row, col as f64 + ROW_OFFSETS[row]));
+            }
+        }
+        None
+    }
+
+    // Keys touch when they sit side by side on a row, or overlap on the row above or below
+    pub fn are_adjacent(&self, letter1: char, letter2: char) -> bool {
+        match (self.position(letter1), self.position(letter2)) {
+            (Some((row1, x1)), Some((row2, x2))) => {
+                if row1 == row2 {
+                    (x1 - x2).abs() == 1.0
+                }
+                else {
+                    row1.abs_diff(row2) == 1 && (x1 - x2).abs() < 1.0
+                }
+            }
+            _ => false,
+        }
+    }
+
+    pub fn substitution_cost(&self, letter1: char, letter2: char) -> f64 {
+        if letter1 == letter2 {
+            0.0
+        }
+        else if self.are_adjacent(letter1, letter2) {
+            ADJACENT_KEY_COST
+        }
+        else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_adjacent_keys() {
+        let layout = KeyboardLayout::Qwerty;
+        assert!(layout.are_adjacent('s', 'a'), "Keys beside each other not adjacent");
+        assert!(layout.are_adjacent('s', 'w'), "Key above not adjacent");
+        assert!(layout.are_adjacent('s', 'e'), "Key above and right not adjacent");
+        assert!(layout.are_adjacent('s', 'x'), "Key below not adjacent");
+        assert!(layout.are_adjacent('s', 'z'), "Key below and left not adjacent");
+        assert!(!layout.are_adjacent('s', 'q'), "Key two columns away found adjacent");
+        assert!(!layout.are_adjacent('s', 'c'), "Key two columns away found adjacent");
+        assert!(!layout.are_adjacent('q', 'z'), "Keys two rows apart found adjacent");
+        assert!(!layout.are_adjacent('s', 's'), "Key found adjacent to itself");
+        assert!(!layout.are_adjacent('s', '1'), "Unknown key found adjacent");
+    }
+
+    #[test]
+    fn test_layouts() {
+        assert!(KeyboardLayout::Qwerty.are_adjacent('x', 'c'), "QWERTY x and c not adjacent");
+        assert!(KeyboardLayout::Azerty.are_adjacent('a', 'z'), "AZERTY a and z not adjacent");
+        assert!(KeyboardLayout::Azerty.are_adjacent('q', 'w'), "AZERTY q and w not adjacent");
+        assert!(KeyboardLayout::Qwertz.are_adjacent('t', 'z'), "QWERTZ t and z not adjacent");
+        assert!(KeyboardLayout::Qwertz.are_adjacent('ö', 'ä'), "QWERTZ ö and ä not adjacent");
+        assert!(!KeyboardLayout::Qwerty.are_adjacent('t', 'z'), "QWERTY t and z found adjacent");
+        assert!(KeyboardLayout::Dvorak.are_adjacent('x', 'b'), "Dvorak x and b not adjacent");
+        assert!(!KeyboardLayout::Dvorak.are_adjacent('x', 'c'), "Dvorak x and c found adjacent");
+    }
+
+    #[test]
+    fn test_substitution_cost() {
+        let layout = KeyboardLayout::Qwerty;
+        assert_eq!(0.0, layout.substitution_cost('a', 'a'), "Same key has a cost");
+        assert_eq!(ADJACENT_KEY_COST, layout.substitution_cost('a', 's'), "Adjacent keys not discounted");
+        assert_eq!(1.0, layout.substitution_cost('a', 'p'), "Distant keys discounted");
+        assert_eq!(1.0, layout.substitution_cost('é', 'e'), "Key missing from layout discounted");
+    }
+}
@@ -0,0 +1,535 @@
+use super::error::SpellError;
+use super::pattern::Pattern;
+use super::trie::{Node, Trie, TrieCursor, Words};
+use memmap2::Mmap;
+use std::collections::{BTreeSet, VecDeque};
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+// Layout, all integers little-endian and every section 8-byte aligned:
+//   header: magic "SPLD", version u32, node count u32, word count u32, total frequency u64
+//   LOUDS bits: a node's children as ones followed by a zero, nodes in breadth-first order,
+//     after "10" for a super-root, packed into u64 words
+//   rank: ones before each u64 word of the bits, as u32
+//   letter, freq and max_freq per node, as u32 each, in breadth-first order
+//   forms: count u32, then each word with capitals it insists on as length u32 and UTF-8
+const MAGIC: &[u8; 4] = b"SPLD";
+const VERSION: u32 = 1;
+const HEADER_LENGTH: usize = 4 + 4 + 4 + 4 + 8;
+
+#[derive(Debug)]
+enum Bytes {
+    Mapped(Mmap),
+    #[cfg(test)]
+    Owned(Vec<u8>),
+}
+
+impl Bytes {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Bytes::Mapped(mmap) => mmap,
+            #[cfg(test)]
+            Bytes::Owned(bytes) => bytes,
+        }
+    }
+}
+
+// A read-only trie queried straight from its file. Nodes are only numbers into flat arrays, so
+// mapping a big dictionary costs no heap and the pages are shared between processes
+#[derive(Debug, Clone)]
+pub struct MappedTrie {
+    bytes: Arc<Bytes>,
+    num_nodes: u32,
+    num_words: u32,
+    total_freq: u64,
+    num_blocks: usize,
+    rank_start: usize,
+    letters_start: usize,
+    freqs_start: usize,
+    max_freqs_start: usize,
+    forms_start: usize,
+}
+
+impl MappedTrie {
+    pub fn open(path: &Path) -> Result<Self, SpellError> {
+        let file = File::open(path)?;
+        // Changing the file while it's mapped would change the trie under us, so dictionaries are
+        // only ever replaced with a new file, never written in place
+        let mmap = unsafe { Mmap::map(&file)? };
+        MappedTrie::from_bytes(Bytes::Mapped(mmap))
+    }
+
+    #[cfg(test)]
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self, SpellError> {
+        MappedTrie::from_bytes(Bytes::Owned(bytes))
+    }
+
+    fn from_bytes(bytes: Bytes) -> Result<Self, SpellError> {
+        let slice = bytes.as_slice();
+        if slice.len() < HEADER_LENGTH || &slice[..4] != MAGIC {
+            return Err(SpellError::InvalidDictionary("not a mapped dictionary"));
+        }
+        if read_u32(slice, 4) != VERSION {
+            return Err(SpellError::InvalidDictionary("unsupported version"));
+        }
+        let num_nodes = read_u32(slice, 8);
+        let num_words = read_u32(slice, 12);
+        let total_freq = read_u64(slice, 16);
+        if num_nodes == 0 {
+            return Err(SpellError::InvalidDictionary("missing root"));
+        }
+
+        let num_blocks = (2 * num_nodes as usize + 1).div_ceil(64);
+        let rank_start = HEADER_LENGTH + num_blocks * 8;
+        let letters_start = rank_start + align(num_blocks * 4);
+        let freqs_start = letters_start + align(num_nodes as usize * 4);
+        let max_freqs_start = freqs_start + align(num_nodes as usize * 4);
+        let forms_start = max_freqs_start + align(num_nodes as usize * 4);
+        if slice.len() < forms_start + 4 {
+            return Err(SpellError::InvalidDictionary("truncated"));
+        }
+
+        let trie = Self {bytes: Arc::new(bytes), num_nodes, num_words, total_freq, num_blocks, rank_start, letters_start, freqs_start, max_freqs_start, forms_start};
+        trie.check_bits()?;
+        trie.check_letters()?;
+        trie.get_forms()?;
+        Ok(trie)
+    }
+
+    // Lookups trust the bits and the rank directory, so a damaged file is caught here instead of
+    // panicking later. Every node's children must come after it, which also rules out cycles,
+    // and there must be one bit per node plus one zero per node and one more
+    fn check_bits(&self) -> Result<(), SpellError> {
+        let slice = self.bytes.as_slice();
+        let num_bits = 2 * self.num_nodes as usize + 1;
+        let mut ones = 0;
+        let mut zeros = 0;
+
+        for block in 0..self.num_blocks {
+            if read_u32(slice, self.rank_start + block * 4) as usize != ones {
+                return Err(SpellError::InvalidDictionary("rank directory mismatch"));
+            }
+            let bits = self.get_bit_block(block);
+            for offset in 0..64 {
+                let pos = block * 64 + offset;
+                let bit = bits >> offset & 1 == 1;
+                if pos >= num_bits {
+                    if bit {
+                        return Err(SpellError::InvalidDictionary("too many nodes"));
+                    }
+                    continue;
+                }
+                if bit {
+                    ones += 1;
+                }
+                else {
+                    // Node number zeros has its children after this zero, numbered from ones on
+                    if ones <= zeros && zeros < self.num_nodes as usize {
+                        return Err(SpellError::InvalidDictionary("child before its parent"));
+                    }
+                    zeros += 1;
+                }
+            }
+        }
+
+        if ones != self.num_nodes as usize || zeros != self.num_nodes as usize + 1 {
+            return Err(SpellError::InvalidDictionary("wrong node count"));
+        }
+        Ok(())
+    }
+
+    // get_child binary searches the siblings, so their letters must be real characters in
+    // ascending order
+    fn check_letters(&self) -> Result<(), SpellError> {
+        let slice = self.bytes.as_slice();
+        for index in 0..self.num_nodes {
+            if char::from_u32(read_u32(slice, self.letters_start + index as usize * 4)).is_none() {
+                return Err(SpellError::InvalidDictionary("invalid letter"));
+            }
+        }
+        for index in 0..self.num_nodes {
+            let children = self.get_child_range(index);
+            if children.clone().zip(children.skip(1)).any(|(a, b)| self.get_letter(a) >= self.get_letter(b)) {
+                return Err(SpellError::InvalidDictionary("siblings out of order"));
+            }
+        }
+        Ok(())
+    }
+
+    // Lays the trie out in the mapped format. forms are the words with capitals they insist on
+    pub fn encode<'a, I: IntoIterator<Item = &'a str>>(trie: &Trie, forms: I) -> Vec<u8> {
+        let mut bits: Vec<u64> = Vec::new();
+        let mut num_bits = 0;
+        let mut push_bit = |bits: &mut Vec<u64>, bit: bool| {
+            if num_bits % 64 == 0 {
+                bits.push(0);
+            }
+            if bit {
+                *bits.last_mut().unwrap() |= 1 << (num_bits % 64);
+            }
+            num_bits += 1;
+        };
+        push_bit(&mut bits, true);
+        push_bit(&mut bits, false);
+
+        let mut letters: Vec<u32> = Vec::new();
+        let mut freqs: Vec<u32> = Vec::new();
+        let mut parents: Vec<usize> = Vec::new();
+        let mut queue: VecDeque<(&Node, usize)> = VecDeque::new();
+        queue.push_back((trie.get_root(), 0));
+
+        while let Some((curr_node, parent)) = queue.pop_front() {
+            letters.push(curr_node.get_value() as u32);
+            freqs.push(curr_node.get_freq());
+            parents.push(parent);
+            let index = letters.len() - 1;
+            for next_node in curr_node.child_nodes() {
+                push_bit(&mut bits, true);
+                queue.push_back((next_node, index));
+            }
+            push_bit(&mut bits, false);
+        }
+
+        // Children always come after their parent, so one backwards pass settles every maximum
+        let mut max_freqs = freqs.clone();
+        for index in (1..max_freqs.len()).rev() {
+            max_freqs[parents[index]] = max_freqs[parents[index]].max(max_freqs[index]);
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(letters.len() as u32).to_le_bytes());
+        out.extend_from_slice(&trie.get_word_count().to_le_bytes());
+        out.extend_from_slice(&trie.iter().map(|(_, freq)| freq as u64).sum::<u64>().to_le_bytes());
+
+        let mut ones = 0;
+        let mut rank = Vec::with_capacity(bits.len());
+        for block in bits.iter() {
+            out.extend_from_slice(&block.to_le_bytes());
+            rank.push(ones);
+            ones += block.count_ones();
+        }
+        for section in [&rank, &letters, &freqs, &max_freqs] {
+            for value in section.iter() {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            out.resize(align(out.len()), 0);
+        }
+
+        let forms: Vec<&str> = forms.into_iter().collect();
+        out.extend_from_slice(&(forms.len() as u32).to_le_bytes());
+        for form in forms {
+            out.extend_from_slice(&(form.len() as u32).to_le_bytes());
+            out.extend_from_slice(form.as_bytes());
+        }
+        out
+    }
+
+    pub fn get_root(&self) -> MappedNode<'_> {
+        MappedNode {trie: self, index: 0}
+    }
+    pub fn get_word_count(&self) -> u32 {
+        self.num_words
+    }
+    pub fn get_node_count(&self) -> u32 {
+        self.num_nodes
+    }
+    pub fn get_total_freq(&self) -> u64 {
+        self.total_freq
+    }
+
+    // Every letter any word uses, straight from the node letters
+    pub fn get_alphabet(&self) -> BTreeSet<char> {
+        (1..self.num_nodes).map(|index| self.get_letter(index)).collect()
+    }
+
+    pub fn get_forms(&self) -> Result<Vec<String>, SpellError> {
+        let slice = self.bytes.as_slice();
+        let num_forms = read_u32(slice, self.forms_start);
+        let mut pos = self.forms_start + 4;
+        let mut out = Vec::with_capacity(num_forms as usize);
+
+        for _ in 0..num_forms {
+            if pos + 4 > slice.len() {
+                return Err(SpellError::InvalidDictionary("truncated"));
+            }
+            let length = read_u32(slice, pos) as usize;
+            pos += 4;
+            let form = slice.get(pos..pos + length).ok_or(SpellError::InvalidDictionary("truncated"))?;
+            out.push(String::from_utf8(form.to_vec()).map_err(|_| SpellError::InvalidDictionary("invalid UTF-8"))?);
+            pos += length;
+        }
+
+        Ok(out)
+    }
+
+    pub fn find(&self, word: &String) -> Option<MappedNode<'_>> {
+        self.get_root().descend(&word.to_lowercase()).filter(|node| node.get_freq() >= 1)
+    }
+
+    pub fn fuzzy_find(&self, word: &String, max_distance: u32) -> Vec<(String, u32)> {
+        self.get_root().fuzzy_find(&word.to_lowercase(), max_distance)
+    }
+
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<(String, u32)> {
+        self.get_root().find_pattern(pattern)
+    }
+
+    pub fn anagrams(&self, letters: &str, partial: bool) -> Vec<(String, u32)> {
+        self.get_root().anagrams(letters, partial)
+    }
+
+    pub fn complete(&self, prefix: &String, k: usize) -> Vec<(String, u32)> {
+        let lower_prefix = prefix.to_lowercase();
+        match self.get_root().descend(&lower_prefix) {
+            Some(curr_node) => curr_node.complete(lower_prefix, k, true),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn iter(&self) -> Words<MappedNode<'_>> {
+        self.get_root().words(String::new())
+    }
+
+    fn get_bit_block(&self, block: usize) -> u64 {
+        read_u64(self.bytes.as_slice(), HEADER_LENGTH + block * 8)
+    }
+
+    // Ones before position pos of the bits
+    fn rank1(&self, pos: usize) -> usize {
+        let block = pos / 64;
+        let before = read_u32(self.bytes.as_slice(), self.rank_start + block * 4) as usize;
+        let mask = (1u64 << (pos % 64)) - 1;
+        before + (self.get_bit_block(block) & mask).count_ones() as usize
+    }
+
+    // Position of the zero with index n, counting from zero
+    fn select0(&self, n: usize) -> usize {
+        let slice = self.bytes.as_slice();
+        let zeros_before = |block: usize| block * 64 - read_u32(slice, self.rank_start + block * 4) as usize;
+
+        // The last block with at most n zeros before it holds the zero
+        let (mut low, mut high) = (0, self.num_blocks);
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if zeros_before(middle) <= n {
+                low = middle;
+            }
+            else {
+                high = middle;
+            }
+        }
+
+        let mut remaining = n - zeros_before(low);
+        let mut zeros = !self.get_bit_block(low);
+        loop {
+            let offset = zeros.trailing_zeros() as usize;
+            if remaining == 0 {
+                return low * 64 + offset;
+            }
+            zeros &= zeros - 1;
+            remaining -= 1;
+        }
+    }
+
+    // Node numbers of the children, which are always consecutive
+    fn get_child_range(&self, index: u32) -> Range<u32> {
+        let start = self.select0(index as usize) + 1;
+        let end = self.select0(index as usize + 1);
+        let first = self.rank1(start) as u32;
+        first..first + (end - start) as u32
+    }
+
+    fn get_letter(&self, index: u32) -> char {
+        char::from_u32(read_u32(self.bytes.as_slice(), self.letters_start + index as usize * 4)).unwrap_or('\0')
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MappedNode<'a> {
+    trie: &'a MappedTrie,
+    index: u32,
+}
+
+pub struct MappedChildren<'a> {
+    trie: &'a MappedTrie,
+    indexes: Range<u32>,
+}
+
+impl<'a> Iterator for MappedChildren<'a> {
+    type Item = MappedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indexes.next().map(|index| MappedNode {trie: self.trie, index})
+    }
+}
+
+impl<'a> TrieCursor for MappedNode<'a> {
+    type Children = MappedChildren<'a>;
+
+    fn get_value(&self) -> char {
+        self.trie.get_letter(self.index)
+    }
+    fn get_freq(&self) -> u32 {
+        read_u32(self.trie.bytes.as_slice(), self.trie.freqs_start + self.index as usize * 4)
+    }
+    fn get_max_freq(&self) -> u32 {
+        read_u32(self.trie.bytes.as_slice(), self.trie.max_freqs_start + self.index as usize * 4)
+    }
+    // Siblings are stored in letter order, so a binary search finds the child
+    fn get_child(&self, letter: char) -> Option<Self> {
+        let mut children = self.trie.get_child_range(self.index);
+        while !children.is_empty() {
+            let middle = children.start + (children.end - children.start) / 2;
+            match self.trie.get_letter(middle).cmp(&letter) {
+                std::cmp::Ordering::Equal => return Some(MappedNode {trie: self.trie, index: middle}),
+                std::cmp::Ordering::Less => children.start = middle + 1,
+                std::cmp::Ordering::Greater => children.end = middle,
+            }
+        }
+        None
+    }
+    fn child_nodes(&self) -> Self::Children {
+        MappedChildren {trie: self.trie, indexes: self.trie.get_child_range(self.index)}
+    }
+}
+
+fn align(length: usize) -> usize {
+    length.div_ceil(8) * 8
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::fs;
+
+    fn setup() -> (Trie, MappedTrie) {
+        let mut trie = Trie::new();
+        for word in ["cares", "caress", "baboon", "car", "car", "café", "zebra", "a"] {
+            trie.add(&word.to_string());
+        }
+        let mapped = MappedTrie::from_vec(MappedTrie::encode(&trie, ["NASA", "iPhone"])).expect("Unable to read back the mapped trie");
+        (trie, mapped)
+    }
+
+    #[test]
+    fn test_lookup() {
+        let (trie, mapped) = setup();
+
+        assert_eq!(trie.get_word_count(), mapped.get_word_count(), "Incorrect word count");
+        assert_eq!(trie.get_node_count(), mapped.get_node_count(), "Incorrect node count");
+        assert_eq!(8, mapped.get_total_freq(), "Incorrect total frequency");
+        assert_eq!(2, mapped.find(&"CAR".to_string()).map_or(0, |node| node.get_freq()), "Incorrect frequency for \"car\"");
+        assert!(mapped.find(&"café".to_string()).is_some(), "Accented word not found");
+        assert!(mapped.find(&"a".to_string()).is_some(), "Single letter word not found");
+        assert!(mapped.find(&"ca".to_string()).is_none(), "Found prefix that isn't a word");
+        assert!(mapped.find(&"dog".to_string()).is_none(), "Found missing word");
+        assert_eq!(vec!["NASA", "iPhone"], mapped.get_forms().unwrap(), "Incorrect capitalized forms");
+        assert_eq!("abcefnorsz", mapped.get_alphabet().into_iter().filter(|letter| letter.is_ascii()).collect::<String>(), "Incorrect alphabet");
+    }
+
+    #[test]
+    fn test_same_as_trie() {
+        let (trie, mapped) = setup();
+
+        assert_eq!(trie.iter().collect::<Vec<_>>(), mapped.iter().collect::<Vec<_>>(), "Words differ from the trie");
+        for word in ["car", "caes", "zebar", "bab", "x"] {
+            let mut expected = trie.fuzzy_find(&word.to_string(), 2);
+            let mut actual = mapped.fuzzy_find(&word.to_string(), 2);
+            expected.sort();
+            actual.sort();
+            assert_eq!(expected, actual, "Fuzzy search differs from the trie for \"{}\"", word);
+        }
+        for prefix in ["ca", "", "z", "q"] {
+            assert_eq!(trie.complete(&prefix.to_string(), 3), mapped.complete(&prefix.to_string(), 3), "Completions differ from the trie for \"{}\"", prefix);
+        }
+    }
+
+    #[test]
+    fn test_large() {
+        let mut trie = Trie::new();
+        for i in 0..3000u32 {
+            let word: String = i.to_string().chars().map(|digit| (b'a' + digit as u8 - b'0') as char).collect();
+            for _ in 0..i % 7 + 1 {
+                trie.add(&word);
+            }
+        }
+        let mapped = MappedTrie::from_vec(MappedTrie::encode(&trie, [])).unwrap();
+
+        assert_eq!(trie.iter().collect::<Vec<_>>(), mapped.iter().collect::<Vec<_>>(), "Words differ from the trie across many bit blocks");
+        assert_eq!(trie.complete(&"b".to_string(), 10), mapped.complete(&"b".to_string(), 10), "Completions differ from the trie across many bit blocks");
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let (trie, _) = setup();
+        let bytes = MappedTrie::encode(&trie, []);
+
+        let mut all_ones = bytes.clone();
+        all_ones[HEADER_LENGTH..HEADER_LENGTH + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(MappedTrie::from_vec(all_ones), Err(SpellError::InvalidDictionary(_))), "Block of all ones accepted");
+
+        let mut cleared = bytes.clone();
+        cleared[HEADER_LENGTH..HEADER_LENGTH + 8].copy_from_slice(&0u64.to_le_bytes());
+        assert!(matches!(MappedTrie::from_vec(cleared), Err(SpellError::InvalidDictionary(_))), "Block of all zeros accepted");
+
+        // Swapping the bits of the super-root makes the root its own child
+        let mut cycle = bytes.clone();
+        cycle[HEADER_LENGTH] ^= 0b11;
+        assert!(matches!(MappedTrie::from_vec(cycle), Err(SpellError::InvalidDictionary(_))), "Node before its parent accepted");
+
+        let num_blocks = (2 * trie.get_node_count() as usize + 1).div_ceil(64);
+        let mut wrong_rank = bytes.clone();
+        let rank_start = HEADER_LENGTH + num_blocks * 8;
+        wrong_rank[rank_start..rank_start + 4].copy_from_slice(&7u32.to_le_bytes());
+        assert!(matches!(MappedTrie::from_vec(wrong_rank), Err(SpellError::InvalidDictionary("rank directory mismatch"))), "Wrong rank directory accepted");
+
+        // The root's children a, b, c and z come right after the root
+        let letters_start = rank_start + align(num_blocks * 4);
+        let mut swapped = bytes.clone();
+        swapped[letters_start + 4..letters_start + 12].rotate_left(4);
+        assert!(matches!(MappedTrie::from_vec(swapped), Err(SpellError::InvalidDictionary("siblings out of order"))), "Siblings out of order accepted");
+
+        let mut surrogate = bytes.clone();
+        surrogate[letters_start + 4..letters_start + 8].copy_from_slice(&0xd800u32.to_le_bytes());
+        assert!(matches!(MappedTrie::from_vec(surrogate), Err(SpellError::InvalidDictionary("invalid letter"))), "Invalid letter accepted");
+
+        let mut wrong_count = bytes.clone();
+        wrong_count[8..12].copy_from_slice(&(trie.get_node_count() - 1).to_le_bytes());
+        assert!(MappedTrie::from_vec(wrong_count).is_err(), "Wrong node count accepted");
+    }
+
+    #[test]
+    fn test_open() {
+        let (trie, _) = setup();
+        let dir = std::env::temp_dir().join(format!("spelling_corrector_mapped_{}", std::process::id()));
+        let path = dir.join("words.map");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(&path, MappedTrie::encode(&trie, [])).unwrap();
+        let mapped = MappedTrie::open(&path).expect("Unable to map dictionary");
+        assert!(mapped.find(&"caress".to_string()).is_some(), "Word not found in mapped file");
+        assert!(mapped.clone().find(&"baboon".to_string()).is_some(), "Word not found in a clone sharing the mapping");
+
+        let text_path = dir.join("words.txt");
+        fs::write(&text_path, "car cares").unwrap();
+        assert!(matches!(MappedTrie::open(&text_path), Err(SpellError::InvalidDictionary(_))), "Text file mapped as a dictionary");
+        let bytes = MappedTrie::encode(&trie, ["NASA"]);
+        assert!(MappedTrie::from_vec(bytes[..bytes.len() - 2].to_vec()).is_err(), "Truncated file accepted");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+use super::error::SpellError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Element {
+    Letter(char),
+    AnyLetter,
+    AnyRun,
+    Class {negated: bool, ranges: Vec<(char, char)>},
+}
+
+impl Element {
+    fn accepts(&self, letter: char) -> bool {
+        match self {
+            Element::Letter(expected) => *expected == letter,
+            Element::AnyLetter | Element::AnyRun => true,
+            Element::Class {negated, ranges} => ranges.iter().any(|(low, high)| (*low..=*high).contains(&letter)) != *negated,
+        }
+    }
+}
+
+// `?` stands for any one letter, `*` for any run of letters including none, and `[...]` for one
+// of the letters listed, with ranges like `a-e`, or any other letter when it opens with `^` or `!`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    elements: Vec<Element>,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, SpellError> {
+        if pattern.is_empty() {
+            return Err(SpellError::EmptyInput);
+        }
+        let lower_pattern = pattern.to_lowercase();
+        let mut letters = lower_pattern.chars().peekable();
+        let mut elements = Vec::new();
+
+        while let Some(letter) = letters.next() {
+            let element = match letter {
+                '?' => Element::AnyLetter,
+                // A run of stars matches no more than one does
+                '*' if elements.last() == Some(&Element::AnyRun) => continue,
+                '*' => Element::AnyRun,
+                '[' => {
+                    let negated = letters.next_if(|letter| *letter == '^' || *letter == '!').is_some();
+                    let mut ranges = Vec::new();
+                    loop {
+                        let low = match letters.next() {
+                            Some(']') if !ranges.is_empty() => break,
+                            Some(']') => return Err(SpellError::InvalidPattern("empty character class")),
+                            Some(low) => low,
+                            None => return Err(SpellError::InvalidPattern("unclosed character class")),
+                        };
+                        let high = match letters.next_if_eq(&'-') {
+                            Some(_) => match letters.next() {
+                                Some(']') | None => return Err(SpellError::InvalidPattern("unfinished range")),
+                                Some(high) => high,
+                            },
+                            None => low,
+                        };
+                        if high < low {
+                            return Err(SpellError::InvalidPattern("reversed range"));
+                        }
+                        ranges.push((low, high));
+                    }
+                    Element::Class {negated, ranges}
+                }
+                ']' => return Err(SpellError::InvalidPattern("unopened character class")),
+                letter if letter.is_whitespace() || letter.is_control() => return Err(SpellError::UnsupportedCharacter(letter)),
+                letter => Element::Letter(letter),
+            };
+            elements.push(element);
+        }
+
+        Ok(Self {elements})
+    }
+
+    // Matching keeps the set of places in the pattern a prefix could have reached, sorted, so a
+    // trie walk can carry it down and drop any branch once it's empty
+    pub fn start(&self) -> Vec<usize> {
+        self.skip_runs(vec![0])
+    }
+
+    pub fn step(&self, positions: &[usize], letter: char) -> Vec<usize> {
+        let mut next = Vec::new();
+        for position in positions.iter() {
+            match self.elements.get(*position) {
+                Some(Element::AnyRun) => next.push(*position),
+                Some(element) if element.accepts(letter) => next.push(position + 1),
+                _ => {}
+            }
+        }
+        self.skip_runs(next)
+    }
+
+    pub fn is_match(&self, positions: &[usize]) -> bool {
+        positions.contains(&self.elements.len())
+    }
+
+    pub fn matches(&self, word: &str) -> bool {
+        let mut positions = self.start();
+        for letter in word.to_lowercase().chars() {
+            positions = self.step(&positions, letter);
+            if positions.is_empty() {
+                return false;
+            }
+        }
+        self.is_match(&positions)
+    }
+
+    // A `*` can match nothing, so the place after it is reached as soon as the `*` is
+    fn skip_runs(&self, mut positions: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < positions.len() {
+            if self.elements.get(positions[i]) == Some(&Element::AnyRun) {
+                positions.push(positions[i] + 1);
+            }
+            i += 1;
+        }
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(4, Pattern::parse("c?[aeiou]*").unwrap().elements.len(), "Incorrect number of elements");
+        assert_eq!(2, Pattern::parse("a***").unwrap().elements.len(), "Runs of stars not merged");
+        assert_eq!(Pattern::parse("cat").unwrap(), Pattern::parse("CAT").unwrap(), "Pattern not lowercased");
+
+        assert!(matches!(Pattern::parse(""), Err(SpellError::EmptyInput)), "Empty pattern accepted");
+        assert!(matches!(Pattern::parse("c[at"), Err(SpellError::InvalidPattern("unclosed character class"))), "Unclosed class accepted");
+        assert!(matches!(Pattern::parse("c[]t"), Err(SpellError::InvalidPattern("empty character class"))), "Empty class accepted");
+        assert!(matches!(Pattern::parse("c[z-a]t"), Err(SpellError::InvalidPattern("reversed range"))), "Reversed range accepted");
+        assert!(matches!(Pattern::parse("c[a-]t"), Err(SpellError::InvalidPattern("unfinished range"))), "Unfinished range accepted");
+        assert!(matches!(Pattern::parse("ca]t"), Err(SpellError::InvalidPattern("unopened character class"))), "Stray bracket accepted");
+        assert!(matches!(Pattern::parse("c t"), Err(SpellError::UnsupportedCharacter(' '))), "Space accepted");
+    }
+
+    #[test]
+    fn test_matches() {
+        let pattern = Pattern::parse("c?t").unwrap();
+        assert!(pattern.matches("cat") && pattern.matches("CUT"), "Question mark did not match one letter");
+        assert!(!pattern.matches("ct") && !pattern.matches("cart"), "Question mark matched other than one letter");
+
+        let pattern = Pattern::parse("*ing").unwrap();
+        assert!(pattern.matches("walking") && pattern.matches("ing"), "Star did not match a run, or nothing");
+        assert!(!pattern.matches("ingot"), "Star pattern matched a word with more after it");
+
+        let pattern = Pattern::parse("c*r*s").unwrap();
+        assert!(pattern.matches("cars") && pattern.matches("cares") && pattern.matches("crs"), "Two stars did not match");
+        assert!(!pattern.matches("scars"), "Star pattern matched a word starting differently");
+
+        let pattern = Pattern::parse("[bc]a[^r]").unwrap();
+        assert!(pattern.matches("bat") && pattern.matches("cab"), "Character class did not match");
+        assert!(!pattern.matches("car") && !pattern.matches("mat"), "Character class matched a letter it excludes");
+
+        let pattern = Pattern::parse("[a-cé]?").unwrap();
+        assert!(pattern.matches("bé") && pattern.matches("éa"), "Range or accented letter in a class did not match");
+        assert!(!pattern.matches("da"), "Range matched a letter outside it");
+        assert!(Pattern::parse("*").unwrap().matches(""), "Lone star did not match the empty word");
+    }
+}
@@ -0,0 +1,238 @@
+use super::error::SpellError;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Marks a removed word in the file. An added word that starts with either mark, like "-ish",
+// is saved behind the escape so it isn't read back as a removal
+const REMOVED_PREFIX: char = '-';
+const ESCAPE_PREFIX: char = '\\';
+
+// The user's own additions to and removals from the main dictionary. Added and removed words are
+// saved to the file after every change; ignored words only last as long as the session
+#[derive(Debug, Clone, Default)]
+pub struct PersonalDictionary {
+    path: Option<PathBuf>,
+    // Lowercase form to the word as the user wrote it
+    added: BTreeMap<String, String>,
+    removed: BTreeSet<String>,
+    ignored: HashSet<String>,
+}
+
+impl PersonalDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A file that does not exist yet is an empty dictionary, created on the first change
+    pub fn load(path: &Path) -> Result<Self, SpellError> {
+        let mut dictionary = Self {path: Some(path.to_path_buf()), ..Self::default()};
+        let file = match fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(dictionary),
+            Err(error) => return Err(error.into()),
+        };
+
+        for line in file.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if let Some(word) = line.strip_prefix(REMOVED_PREFIX) {
+                dictionary.removed.insert(word.to_lowercase());
+                continue;
+            }
+            let word = line.strip_prefix(ESCAPE_PREFIX).unwrap_or(line);
+            dictionary.added.insert(word.to_lowercase(), word.to_string());
+        }
+
+        Ok(dictionary)
+    }
+
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn get_added_words(&self) -> Vec<&str> {
+        self.added.values().map(|word| word.as_str()).collect()
+    }
+
+    pub fn get_removed_count(&self) -> usize {
+        self.removed.len()
+    }
+
+    pub fn is_removed(&self, lower_word: &str) -> bool {
+        self.removed.contains(lower_word)
+    }
+
+    pub fn is_ignored(&self, lower_word: &str) -> bool {
+        self.ignored.contains(lower_word)
+    }
+
+    // A change that can't be saved is undone, so the words in memory always match the file
+    pub fn add(&mut self, word: &str) -> Result<(), SpellError> {
+        let lower_word = word.to_lowercase();
+        let was_removed = self.removed.remove(&lower_word);
+        let previous = self.added.insert(lower_word.clone(), word.to_string());
+        if let Err(error) = self.save() {
+            match previous {
+                Some(previous) => self.added.insert(lower_word.clone(), previous),
+                None => self.added.remove(&lower_word),
+            };
+            if was_removed {
+                self.removed.insert(lower_word);
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, word: &str) -> Result<(), SpellError> {
+        let lower_word = word.to_lowercase();
+        let previous = self.added.remove(&lower_word);
+        let was_ignored = self.ignored.remove(&lower_word);
+        let was_removed = !self.removed.insert(lower_word.clone());
+        if let Err(error) = self.save() {
+            if let Some(previous) = previous {
+                self.added.insert(lower_word.clone(), previous);
+            }
+            if was_ignored {
+                self.ignored.insert(lower_word.clone());
+            }
+            if !was_removed {
+                self.removed.remove(&lower_word);
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    pub fn ignore(&mut self, word: &str) {
+        self.ignored.insert(word.to_lowercase());
+    }
+
+    fn save(&self) -> Result<(), SpellError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(SpellError::Write)?;
+        }
+
+        let mut file = String::new();
+        for word in self.added.values() {
+            if word.starts_with([REMOVED_PREFIX, ESCAPE_PREFIX]) {
+                file.push(ESCAPE_PREFIX);
+            }
+            file.push_str(word);
+            file.push('\n');
+        }
+        for word in self.removed.iter() {
+            file.push(REMOVED_PREFIX);
+            file.push_str(word);
+            file.push('\n');
+        }
+        fs::write(path, file).map_err(SpellError::Write)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::env;
+
+    fn setup(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("spelling_corrector_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("personal").join("words.txt")
+    }
+
+    #[test]
+    fn test_add_remove_ignore() {
+        let mut dictionary = PersonalDictionary::new();
+        dictionary.add("Rustacean").unwrap();
+        dictionary.add("yeet").unwrap();
+        dictionary.remove("yea").unwrap();
+        dictionary.ignore("Lol");
+
+        assert_eq!(vec!["Rustacean", "yeet"], dictionary.get_added_words(), "Incorrect added words");
+        assert!(dictionary.is_removed("yea"), "Removed word not recorded");
+        assert_eq!(1, dictionary.get_removed_count(), "Incorrect removed count");
+        assert!(dictionary.is_ignored("lol"), "Ignored word not recorded in lowercase");
+        assert!(dictionary.get_path().is_none(), "In-memory dictionary has a path");
+
+        dictionary.remove("YEET").unwrap();
+        assert_eq!(vec!["Rustacean"], dictionary.get_added_words(), "Removing an added word kept it");
+        assert!(dictionary.is_removed("yeet"), "Removed added word not recorded");
+
+        dictionary.add("yea").unwrap();
+        assert!(!dictionary.is_removed("yea"), "Adding a removed word kept it removed");
+    }
+
+    #[test]
+    fn test_load_save() {
+        let path = setup("load_save");
+
+        let mut dictionary = PersonalDictionary::load(&path).expect("Missing file not treated as empty");
+        assert!(dictionary.get_added_words().is_empty(), "Found words in missing file");
+        assert!(!path.exists(), "File created before any change");
+
+        dictionary.add("Rustacean").unwrap();
+        dictionary.add("borrowck").unwrap();
+        dictionary.remove("yea").unwrap();
+        dictionary.ignore("lol");
+        assert_eq!("borrowck\nRustacean\n-yea\n", fs::read_to_string(&path).unwrap(), "Incorrect file contents");
+
+        let reloaded = PersonalDictionary::load(&path).unwrap();
+        assert_eq!(vec!["borrowck", "Rustacean"], reloaded.get_added_words(), "Added words not persisted");
+        assert!(reloaded.is_removed("yea"), "Removed words not persisted");
+        assert!(!reloaded.is_ignored("lol"), "Ignored words persisted past the session");
+        assert_eq!(Some(path.as_path()), reloaded.get_path(), "Incorrect path");
+
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_save_failure() {
+        let path = setup("save_failure");
+        let mut dictionary = PersonalDictionary::load(&path).unwrap();
+        dictionary.add("borrowck").unwrap();
+        dictionary.remove("yea").unwrap();
+        dictionary.ignore("lol");
+        let blocker = path.parent().unwrap();
+        fs::remove_dir_all(blocker).unwrap();
+        fs::write(blocker, "not a directory").unwrap();
+
+        let error = dictionary.add("Rustacean").unwrap_err();
+        assert!(matches!(error, SpellError::Write(_)), "Failed save not reported as a write error");
+        assert!(error.to_string().starts_with("Unable to save"), "Failed save reported as a failed read");
+        assert_eq!(vec!["borrowck"], dictionary.get_added_words(), "Added word kept after a failed save");
+
+        assert!(dictionary.add("yea").is_err(), "Failed save not reported");
+        assert!(dictionary.is_removed("yea"), "Removed word restored after a failed save");
+        assert!(dictionary.remove("borrowck").is_err() && dictionary.remove("lol").is_err(), "Failed save not reported");
+        assert_eq!(vec!["borrowck"], dictionary.get_added_words(), "Word removed after a failed save");
+        assert!(!dictionary.is_removed("borrowck") && !dictionary.is_removed("lol"), "Removal kept after a failed save");
+        assert!(dictionary.is_ignored("lol"), "Ignored word forgotten after a failed save");
+
+        fs::remove_dir_all(blocker.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_marked_words() {
+        let path = setup("marked_words");
+
+        let mut dictionary = PersonalDictionary::load(&path).unwrap();
+        dictionary.add("-ish").unwrap();
+        dictionary.add("\\n").unwrap();
+        dictionary.remove("-ly").unwrap();
+        assert_eq!("\\-ish\n\\\\n\n--ly\n", fs::read_to_string(&path).unwrap(), "Marked words not escaped");
+
+        let reloaded = PersonalDictionary::load(&path).unwrap();
+        assert_eq!(vec!["-ish", "\\n"], reloaded.get_added_words(), "Added word starting with a mark not round-tripped");
+        assert!(!reloaded.is_removed("ish"), "Added word starting with a dash read back as a removal");
+        assert!(reloaded.is_removed("-ly"), "Removed word starting with a dash not round-tripped");
+
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+}
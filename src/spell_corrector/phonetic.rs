@@ -0,0 +1,737 @@
+use std::collections::HashMap;
+
+const METAPHONE_LENGTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneticEncoder {
+    Soundex,
+    DoubleMetaphone,
+}
+
+impl PhoneticEncoder {
+    // Every key the word is filed under; Double Metaphone gives an alternate pronunciation
+    // whenever the spelling is ambiguous
+    pub fn encode(&self, word: &str) -> Vec<String> {
+        let mut keys = match self {
+            PhoneticEncoder::Soundex => vec![soundex(word)],
+            PhoneticEncoder::DoubleMetaphone => {
+                let (primary, alternate) = double_metaphone(word);
+                vec![primary, alternate]
+            }
+        };
+        keys.retain(|key| !key.is_empty());
+        keys.dedup();
+        keys
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PhoneticIndex {
+    encoder: PhoneticEncoder,
+    keys: HashMap<String, Vec<String>>,
+}
+
+impl PhoneticIndex {
+    pub fn new(encoder: PhoneticEncoder) -> Self {
+        Self {encoder, keys: HashMap::new()}
+    }
+
+    pub fn get_encoder(&self) -> PhoneticEncoder {
+        self.encoder
+    }
+
+    pub fn add(&mut self, word: &str) {
+        for key in self.encoder.encode(word) {
+            self.keys.entry(key).or_default().push(word.to_string());
+        }
+    }
+
+    pub fn lookup(&self, word: &str) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for key in self.encoder.encode(word) {
+            if let Some(words) = self.keys.get(&key) {
+                out.extend(words.iter().cloned());
+            }
+        }
+        out.sort();
+        out.dedup();
+        out
+    }
+}
+
+pub fn soundex(word: &str) -> String {
+    fn code(letter: char) -> Option<char> {
+        match letter {
+            'B' | 'F' | 'P' | 'V' => Some('1'),
+            'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+            'D' | 'T' => Some('3'),
+            'L' => Some('4'),
+            'M' | 'N' => Some('5'),
+            'R' => Some('6'),
+            _ => None,
+        }
+    }
+
+    let mut letters = word.chars().flat_map(|letter| letter.to_uppercase()).filter(|letter| letter.is_ascii_alphabetic());
+    let first = match letters.next() {
+        Some(letter) => letter,
+        None => return String::new(),
+    };
+
+    let mut out = first.to_string();
+    let mut last_code = code(first);
+    for letter in letters {
+        let letter_code = code(letter);
+        if let Some(digit) = letter_code {
+            if letter_code != last_code {
+                out.push(digit);
+                if out.len() == 4 {
+                    break;
+                }
+            }
+        }
+        // H and W do not separate letters with the same code, vowels do
+        if letter != 'H' && letter != 'W' {
+            last_code = letter_code;
+        }
+    }
+
+    while out.len() < 4 {
+        out.push('0');
+    }
+    out
+}
+
+pub fn double_metaphone(word: &str) -> (String, String) {
+    let mut encoder = DoubleMetaphone {letters: word.chars().flat_map(|letter| letter.to_uppercase()).collect(), primary: String::new(), alternate: String::new()};
+    encoder.encode();
+    encoder.primary.truncate(METAPHONE_LENGTH);
+    encoder.alternate.truncate(METAPHONE_LENGTH);
+    (encoder.primary, encoder.alternate)
+}
+
+// Lawrence Philips' Double Metaphone, following the rule order of the reference implementation
+struct DoubleMetaphone {
+    letters: Vec<char>,
+    primary: String,
+    alternate: String,
+}
+
+impl DoubleMetaphone {
+    fn len(&self) -> isize {
+        self.letters.len() as isize
+    }
+
+    fn char_at(&self, index: isize) -> char {
+        if index < 0 || index >= self.len() {
+            return '\0';
+        }
+        self.letters[index as usize]
+    }
+
+    fn contains(&self, start: isize, length: isize, options: &[&str]) -> bool {
+        if start < 0 || start + length > self.len() {
+            return false;
+        }
+        let target: String = self.letters[start as usize..(start + length) as usize].iter().collect();
+        options.contains(&target.as_str())
+    }
+
+    fn is_vowel(&self, index: isize) -> bool {
+        "AEIOUYÀÁÂÃÄÅÆÈÉÊËÌÍÎÏÒÓÔÕÖØÙÚÛÜÝ".contains(self.char_at(index)) && self.char_at(index) != '\0'
+    }
+
+    fn is_slavo_germanic(&self) -> bool {
+        let word: String = self.letters.iter().collect();
+        word.contains('W') || word.contains('K') || word.contains("CZ") || word.contains("WITZ")
+    }
+
+    fn append(&mut self, primary: &str, alternate: &str) {
+        self.primary.push_str(primary);
+        self.alternate.push_str(alternate);
+    }
+
+    fn append_both(&mut self, value: &str) {
+        self.append(value, value);
+    }
+
+    fn done(&self) -> bool {
+        self.primary.len() >= METAPHONE_LENGTH && self.alternate.len() >= METAPHONE_LENGTH
+    }
+
+    fn encode(&mut self) {
+        let mut index: isize = 0;
+        if self.contains(0, 2, &["GN", "KN", "PN", "WR", "PS"]) {
+            index = 1;
+        }
+        if self.char_at(0) == 'X' {
+            self.append_both("S");
+            index = 1;
+        }
+
+        while !self.done() && index < self.len() {
+            let letter = self.char_at(index);
+            index = match letter {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' | 'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Æ' | 'È' | 'É' | 'Ê' | 'Ë' | 'Ì' | 'Í' | 'Î' | 'Ï' | 'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ý' => {
+                    if index == 0 {
+                        self.append_both("A");
+                    }
+                    index + 1
+                }
+                'B' => {
+                    self.append_both("P");
+                    if self.char_at(index + 1) == 'B' {index + 2} else {index + 1}
+                }
+                'Ç' => {
+                    self.append_both("S");
+                    index + 1
+                }
+                'C' => self.handle_c(index),
+                'D' => self.handle_d(index),
+                'F' => {
+                    self.append_both("F");
+                    if self.char_at(index + 1) == 'F' {index + 2} else {index + 1}
+                }
+                'G' => self.handle_g(index),
+                'H' => self.handle_h(index),
+                'J' => self.handle_j(index),
+                'K' => {
+                    self.append_both("K");
+                    if self.char_at(index + 1) == 'K' {index + 2} else {index + 1}
+                }
+                'L' => self.handle_l(index),
+                'M' => {
+                    self.append_both("M");
+                    if self.condition_m0(index) {index + 2} else {index + 1}
+                }
+                'N' => {
+                    self.append_both("N");
+                    if self.char_at(index + 1) == 'N' {index + 2} else {index + 1}
+                }
+                'Ñ' => {
+                    self.append_both("N");
+                    index + 1
+                }
+                'P' => self.handle_p(index),
+                'Q' => {
+                    self.append_both("K");
+                    if self.char_at(index + 1) == 'Q' {index + 2} else {index + 1}
+                }
+                'R' => self.handle_r(index),
+                'S' => self.handle_s(index),
+                'T' => self.handle_t(index),
+                'V' => {
+                    self.append_both("F");
+                    if self.char_at(index + 1) == 'V' {index + 2} else {index + 1}
+                }
+                'W' => self.handle_w(index),
+                'X' => self.handle_x(index),
+                'Z' => self.handle_z(index),
+                _ => index + 1,
+            };
+        }
+    }
+
+    fn handle_c(&mut self, index: isize) -> isize {
+        if self.condition_c0(index) {
+            self.append_both("K");
+            index + 2
+        }
+        else if index == 0 && self.contains(index, 6, &["CAESAR"]) {
+            self.append_both("S");
+            index + 2
+        }
+        else if self.contains(index, 2, &["CH"]) {
+            self.handle_ch(index)
+        }
+        else if self.contains(index, 2, &["CZ"]) && !self.contains(index - 2, 4, &["WICZ"]) {
+            self.append("S", "X");
+            index + 2
+        }
+        else if self.contains(index + 1, 3, &["CIA"]) {
+            self.append_both("X");
+            index + 3
+        }
+        else if self.contains(index, 2, &["CC"]) && !(index == 1 && self.char_at(0) == 'M') {
+            self.handle_cc(index)
+        }
+        else if self.contains(index, 2, &["CK", "CG", "CQ"]) {
+            self.append_both("K");
+            index + 2
+        }
+        else if self.contains(index, 2, &["CI", "CE", "CY"]) {
+            if self.contains(index, 3, &["CIO", "CIE", "CIA"]) {
+                self.append("S", "X");
+            }
+            else {
+                self.append_both("S");
+            }
+            index + 2
+        }
+        else {
+            self.append_both("K");
+            if self.contains(index + 1, 2, &[" C", " Q", " G"]) {
+                index + 3
+            }
+            else if self.contains(index + 1, 1, &["C", "K", "Q"]) && !self.contains(index + 1, 2, &["CE", "CI"]) {
+                index + 2
+            }
+            else {
+                index + 1
+            }
+        }
+    }
+
+    fn handle_cc(&mut self, index: isize) -> isize {
+        if self.contains(index + 2, 1, &["I", "E", "H"]) && !self.contains(index + 2, 2, &["HU"]) {
+            if (index == 1 && self.char_at(index - 1) == 'A') || self.contains(index - 1, 5, &["UCCEE", "UCCES"]) {
+                self.append_both("KS");
+            }
+            else {
+                self.append_both("X");
+            }
+            index + 3
+        }
+        else {
+            self.append_both("K");
+            index + 2
+        }
+    }
+
+    fn handle_ch(&mut self, index: isize) -> isize {
+        if index > 0 && self.contains(index, 4, &["CHAE"]) {
+            self.append("K", "X");
+        }
+        else if self.condition_ch0(index) || self.condition_ch1(index) {
+            self.append_both("K");
+        }
+        else if index > 0 {
+            if self.contains(0, 2, &["MC"]) {
+                self.append_both("K");
+            }
+            else {
+                self.append("X", "K");
+            }
+        }
+        else {
+            self.append_both("X");
+        }
+        index + 2
+    }
+
+    fn handle_d(&mut self, index: isize) -> isize {
+        if self.contains(index, 2, &["DG"]) {
+            if self.contains(index + 2, 1, &["I", "E", "Y"]) {
+                self.append_both("J");
+                index + 3
+            }
+            else {
+                self.append_both("TK");
+                index + 2
+            }
+        }
+        else if self.contains(index, 2, &["DT", "DD"]) {
+            self.append_both("T");
+            index + 2
+        }
+        else {
+            self.append_both("T");
+            index + 1
+        }
+    }
+
+    fn handle_g(&mut self, index: isize) -> isize {
+        let slavo_germanic = self.is_slavo_germanic();
+        if self.char_at(index + 1) == 'H' {
+            self.handle_gh(index)
+        }
+        else if self.char_at(index + 1) == 'N' {
+            if index == 1 && self.is_vowel(0) && !slavo_germanic {
+                self.append("KN", "N");
+            }
+            else if !self.contains(index + 2, 2, &["EY"]) && self.char_at(index + 1) != 'Y' && !slavo_germanic {
+                self.append("N", "KN");
+            }
+            else {
+                self.append_both("KN");
+            }
+            index + 2
+        }
+        else if self.contains(index + 1, 2, &["LI"]) && !slavo_germanic {
+            self.append("KL", "L");
+            index + 2
+        }
+        else if (index == 0 && (self.char_at(index + 1) == 'Y' || self.contains(index + 1, 2, &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"])))
+            || ((self.contains(index + 1, 2, &["ER"]) || self.char_at(index + 1) == 'Y')
+                && !self.contains(0, 6, &["DANGER", "RANGER", "MANGER"])
+                && !self.contains(index - 1, 1, &["E", "I"])
+                && !self.contains(index - 1, 3, &["RGY", "OGY"])) {
+            self.append("K", "J");
+            index + 2
+        }
+        else if self.contains(index + 1, 1, &["E", "I", "Y"]) || self.contains(index - 1, 4, &["AGGI", "OGGI"]) {
+            if self.contains(0, 4, &["VAN ", "VON "]) || self.contains(0, 3, &["SCH"]) || self.contains(index + 1, 2, &["ET"]) {
+                self.append_both("K");
+            }
+            else if self.contains(index + 1, 3, &["IER"]) {
+                self.append_both("J");
+            }
+            else {
+                self.append("J", "K");
+            }
+            index + 2
+        }
+        else if self.char_at(index + 1) == 'G' {
+            self.append_both("K");
+            index + 2
+        }
+        else {
+            self.append_both("K");
+            index + 1
+        }
+    }
+
+    fn handle_gh(&mut self, index: isize) -> isize {
+        if index > 0 && !self.is_vowel(index - 1) {
+            self.append_both("K");
+        }
+        else if index == 0 {
+            if self.char_at(index + 2) == 'I' {
+                self.append_both("J");
+            }
+            else {
+                self.append_both("K");
+            }
+        }
+        else if (index > 1 && self.contains(index - 2, 1, &["B", "H", "D"]))
+            || (index > 2 && self.contains(index - 3, 1, &["B", "H", "D"]))
+            || (index > 3 && self.contains(index - 4, 1, &["B", "H"])) {
+            // Silent, as in "bough" and "broughton"
+        }
+        else if index > 2 && self.char_at(index - 1) == 'U' && self.contains(index - 3, 1, &["C", "G", "L", "R", "T"]) {
+            self.append_both("F");
+        }
+        else if index > 0 && self.char_at(index - 1) != 'I' {
+            self.append_both("K");
+        }
+        index + 2
+    }
+
+    fn handle_h(&mut self, index: isize) -> isize {
+        if (index == 0 || self.is_vowel(index - 1)) && self.is_vowel(index + 1) {
+            self.append_both("H");
+            index + 2
+        }
+        else {
+            index + 1
+        }
+    }
+
+    fn handle_j(&mut self, index: isize) -> isize {
+        if self.contains(index, 4, &["JOSE"]) || self.contains(0, 4, &["SAN "]) {
+            if (index == 0 && self.char_at(index + 4) == ' ') || self.len() == 4 || self.contains(0, 4, &["SAN "]) {
+                self.append_both("H");
+            }
+            else {
+                self.append("J", "H");
+            }
+            index + 1
+        }
+        else {
+            if index == 0 {
+                self.append("J", "A");
+            }
+            else if self.is_vowel(index - 1) && !self.is_slavo_germanic() && (self.char_at(index + 1) == 'A' || self.char_at(index + 1) == 'O') {
+                self.append("J", "H");
+            }
+            else if index == self.len() - 1 {
+                self.append("J", "");
+            }
+            else if !self.contains(index + 1, 1, &["L", "T", "K", "S", "N", "M", "B", "Z"]) && !self.contains(index - 1, 1, &["S", "K", "L"]) {
+                self.append_both("J");
+            }
+            if self.char_at(index + 1) == 'J' {index + 2} else {index + 1}
+        }
+    }
+
+    fn handle_l(&mut self, index: isize) -> isize {
+        if self.char_at(index + 1) == 'L' {
+            if self.condition_l0(index) {
+                self.append("L", "");
+            }
+            else {
+                self.append_both("L");
+            }
+            index + 2
+        }
+        else {
+            self.append_both("L");
+            index + 1
+        }
+    }
+
+    fn handle_p(&mut self, index: isize) -> isize {
+        if self.char_at(index + 1) == 'H' {
+            self.append_both("F");
+            index + 2
+        }
+        else {
+            self.append_both("P");
+            if self.contains(index + 1, 1, &["P", "B"]) {index + 2} else {index + 1}
+        }
+    }
+
+    fn handle_r(&mut self, index: isize) -> isize {
+        if index == self.len() - 1 && !self.is_slavo_germanic() && self.contains(index - 2, 2, &["IE"]) && !self.contains(index - 4, 2, &["ME", "MA"]) {
+            self.append("", "R");
+        }
+        else {
+            self.append_both("R");
+        }
+        if self.char_at(index + 1) == 'R' {index + 2} else {index + 1}
+    }
+
+    fn handle_s(&mut self, index: isize) -> isize {
+        if self.contains(index - 1, 3, &["ISL", "YSL"]) {
+            index + 1
+        }
+        else if index == 0 && self.contains(index, 5, &["SUGAR"]) {
+            self.append("X", "S");
+            index + 1
+        }
+        else if self.contains(index, 2, &["SH"]) {
+            if self.contains(index + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.append_both("S");
+            }
+            else {
+                self.append_both("X");
+            }
+            index + 2
+        }
+        else if self.contains(index, 3, &["SIO", "SIA"]) || self.contains(index, 4, &["SIAN"]) {
+            if self.is_slavo_germanic() {
+                self.append_both("S");
+            }
+            else {
+                self.append("S", "X");
+            }
+            index + 3
+        }
+        else if (index == 0 && self.contains(index + 1, 1, &["M", "N", "L", "W"])) || self.contains(index + 1, 1, &["Z"]) {
+            self.append("S", "X");
+            if self.contains(index + 1, 1, &["Z"]) {index + 2} else {index + 1}
+        }
+        else if self.contains(index, 2, &["SC"]) {
+            self.handle_sc(index)
+        }
+        else {
+            if index == self.len() - 1 && self.contains(index - 2, 2, &["AI", "OI"]) {
+                self.append("", "S");
+            }
+            else {
+                self.append_both("S");
+            }
+            if self.contains(index + 1, 1, &["S", "Z"]) {index + 2} else {index + 1}
+        }
+    }
+
+    fn handle_sc(&mut self, index: isize) -> isize {
+        if self.char_at(index + 2) == 'H' {
+            if self.contains(index + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                if self.contains(index + 3, 2, &["ER", "EN"]) {
+                    self.append("X", "SK");
+                }
+                else {
+                    self.append_both("SK");
+                }
+            }
+            else if index == 0 && !self.is_vowel(3) && self.char_at(3) != 'W' {
+                self.append("X", "S");
+            }
+            else {
+                self.append_both("X");
+            }
+        }
+        else if self.contains(index + 2, 1, &["I", "E", "Y"]) {
+            self.append_both("S");
+        }
+        else {
+            self.append_both("SK");
+        }
+        index + 3
+    }
+
+    fn handle_t(&mut self, index: isize) -> isize {
+        if self.contains(index, 4, &["TION"]) || self.contains(index, 3, &["TIA", "TCH"]) {
+            self.append_both("X");
+            index + 3
+        }
+        else if self.contains(index, 2, &["TH"]) || self.contains(index, 3, &["TTH"]) {
+            if self.contains(index + 2, 2, &["OM", "AM"]) || self.contains(0, 4, &["VAN ", "VON "]) || self.contains(0, 3, &["SCH"]) {
+                self.append_both("T");
+            }
+            else {
+                self.append("0", "T");
+            }
+            index + 2
+        }
+        else {
+            self.append_both("T");
+            if self.contains(index + 1, 1, &["T", "D"]) {index + 2} else {index + 1}
+        }
+    }
+
+    fn handle_w(&mut self, index: isize) -> isize {
+        if self.contains(index, 2, &["WR"]) {
+            self.append_both("R");
+            index + 2
+        }
+        else if index == 0 && (self.is_vowel(index + 1) || self.contains(index, 2, &["WH"])) {
+            if self.is_vowel(index + 1) {
+                self.append("A", "F");
+            }
+            else {
+                self.append_both("A");
+            }
+            index + 1
+        }
+        else if (index == self.len() - 1 && self.is_vowel(index - 1))
+            || self.contains(index - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.contains(0, 3, &["SCH"]) {
+            self.append("", "F");
+            index + 1
+        }
+        else if self.contains(index, 4, &["WICZ", "WITZ"]) {
+            self.append("TS", "FX");
+            index + 4
+        }
+        else {
+            index + 1
+        }
+    }
+
+    fn handle_x(&mut self, index: isize) -> isize {
+        if index == 0 {
+            self.append_both("S");
+            return index + 1;
+        }
+        // Silent at the end of French words like "breaux"
+        if !(index == self.len() - 1 && (self.contains(index - 3, 3, &["IAU", "EAU"]) || self.contains(index - 2, 2, &["AU", "OU"]))) {
+            self.append_both("KS");
+        }
+        if self.contains(index + 1, 1, &["C", "X"]) {index + 2} else {index + 1}
+    }
+
+    fn handle_z(&mut self, index: isize) -> isize {
+        if self.char_at(index + 1) == 'H' {
+            self.append_both("J");
+            return index + 2;
+        }
+        if self.contains(index + 1, 2, &["ZO", "ZI", "ZA"]) || (self.is_slavo_germanic() && index > 0 && self.char_at(index - 1) != 'T') {
+            self.append("S", "TS");
+        }
+        else {
+            self.append_both("S");
+        }
+        if self.char_at(index + 1) == 'Z' {index + 2} else {index + 1}
+    }
+
+    fn condition_c0(&self, index: isize) -> bool {
+        if self.contains(index, 4, &["CHIA"]) {
+            true
+        }
+        else if index <= 1 || self.is_vowel(index - 2) || !self.contains(index - 1, 3, &["ACH"]) {
+            false
+        }
+        else {
+            let letter = self.char_at(index + 2);
+            (letter != 'I' && letter != 'E') || self.contains(index - 2, 6, &["BACHER", "MACHER"])
+        }
+    }
+
+    fn condition_ch0(&self, index: isize) -> bool {
+        index == 0
+            && (self.contains(index + 1, 5, &["HARAC", "HARIS"]) || self.contains(index + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+            && !self.contains(0, 5, &["CHORE"])
+    }
+
+    fn condition_ch1(&self, index: isize) -> bool {
+        self.contains(0, 4, &["VAN ", "VON "])
+            || self.contains(0, 3, &["SCH"])
+            || self.contains(index - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.contains(index + 2, 1, &["T", "S"])
+            || ((self.contains(index - 1, 1, &["A", "O", "U", "E"]) || index == 0)
+                && (self.contains(index + 2, 1, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "]) || index + 1 == self.len() - 1))
+    }
+
+    fn condition_l0(&self, index: isize) -> bool {
+        if index == self.len() - 3 && self.contains(index - 1, 4, &["ILLO", "ILLA", "ALLE"]) {
+            true
+        }
+        else {
+            (self.contains(self.len() - 2, 2, &["AS", "OS"]) || self.contains(self.len() - 1, 1, &["A", "O"]))
+                && self.contains(index - 1, 4, &["ALLE"])
+        }
+    }
+
+    fn condition_m0(&self, index: isize) -> bool {
+        if self.char_at(index + 1) == 'M' {
+            true
+        }
+        else {
+            self.contains(index - 1, 3, &["UMB"]) && (index + 1 == self.len() - 1 || self.contains(index + 2, 2, &["ER"]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!("R163", soundex("Robert"), "Incorrect Soundex for \"Robert\"");
+        assert_eq!("R163", soundex("Rupert"), "Incorrect Soundex for \"Rupert\"");
+        assert_eq!("R150", soundex("Rubin"), "Incorrect Soundex for \"Rubin\"");
+        assert_eq!("A261", soundex("Ashcraft"), "H should not separate letters with the same code");
+        assert_eq!("T522", soundex("Tymczak"), "Incorrect Soundex for \"Tymczak\"");
+        assert_eq!("P236", soundex("Pfister"), "First letter's code should absorb the next letter");
+        assert_eq!("H555", soundex("Honeyman"), "Vowels should separate letters with the same code");
+        assert_eq!("", soundex("1'2"), "Word without letters has a Soundex key");
+    }
+
+    #[test]
+    fn test_double_metaphone() {
+        assert_eq!(("TMPS".to_string(), "TMPS".to_string()), double_metaphone("Thompson"), "Incorrect Double Metaphone for \"Thompson\"");
+        assert_eq!(("XMT".to_string(), "SMT".to_string()), double_metaphone("Schmidt"), "Incorrect Double Metaphone for \"Schmidt\"");
+        assert_eq!(("SM0".to_string(), "XMT".to_string()), double_metaphone("Smith"), "Incorrect Double Metaphone for \"Smith\"");
+        assert_eq!(("NT".to_string(), "NT".to_string()), double_metaphone("knight"), "Silent K and GH not dropped");
+        assert_eq!(("NT".to_string(), "NT".to_string()), double_metaphone("nite"), "Incorrect Double Metaphone for \"nite\"");
+        assert_eq!(("FNTK".to_string(), "FNTK".to_string()), double_metaphone("phonetic"), "PH not encoded as F");
+        assert_eq!(("FNTK".to_string(), "FNTK".to_string()), double_metaphone("fonetik"), "Incorrect Double Metaphone for \"fonetik\"");
+        assert_eq!(("KF".to_string(), "KF".to_string()), double_metaphone("cough"), "GH after OU not encoded as F");
+        assert_eq!(("JRJ".to_string(), "KRK".to_string()), double_metaphone("George"), "Soft and hard G not both offered");
+        assert_eq!(("AKST".to_string(), "AKST".to_string()), double_metaphone("accident"), "Incorrect Double Metaphone for \"accident\"");
+        assert_eq!(("KF".to_string(), "KF".to_string()), double_metaphone("café"), "Accented vowel not treated as a vowel");
+        assert_eq!((String::new(), String::new()), double_metaphone(""), "Empty word has a Double Metaphone key");
+    }
+
+    #[test]
+    fn test_phonetic_index() {
+        let mut index = PhoneticIndex::new(PhoneticEncoder::DoubleMetaphone);
+        for word in ["night", "knight", "phonetic", "cough", "george"] {
+            index.add(word);
+        }
+
+        assert_eq!(vec!["knight".to_string(), "night".to_string()], index.lookup("nite"), "Words sounding like \"nite\" not found");
+        assert_eq!(vec!["phonetic".to_string()], index.lookup("fonetik"), "Words sounding like \"fonetik\" not found");
+        assert_eq!(vec!["george".to_string()], index.lookup("jorj"), "Alternate encoding not indexed");
+        assert!(index.lookup("banana").is_empty(), "Found words for dissimilar sound");
+        assert_eq!(PhoneticEncoder::DoubleMetaphone, index.get_encoder(), "Incorrect encoder");
+
+        let mut index = PhoneticIndex::new(PhoneticEncoder::Soundex);
+        index.add("robert");
+        assert_eq!(vec!["robert".to_string()], index.lookup("rupert"), "Words with the same Soundex key not found");
+    }
+}
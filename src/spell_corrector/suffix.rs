@@ -0,0 +1,106 @@
+use super::trie::{Trie, TrieCursor};
+
+// Every word spelled backwards, so the words sharing an ending share a path from the root and
+// can be found without scanning the whole dictionary
+#[derive(Debug, Clone)]
+pub struct SuffixIndex {
+    reversed: Trie,
+}
+
+impl SuffixIndex {
+    pub fn new() -> Self {
+        Self {reversed: Trie::new()}
+    }
+
+    pub fn add(&mut self, word: &str, freq: u32) {
+        self.reversed.set_freq(&reverse(word), freq);
+    }
+
+    // Words ending with the suffix, the suffix itself included when it's a word
+    pub fn ends_with(&self, suffix: &str) -> Vec<(String, u32)> {
+        let reversed_suffix = reverse(suffix);
+        let curr_node = match TrieCursor::descend(self.reversed.get_root(), &reversed_suffix) {
+            Some(curr_node) => curr_node,
+            None => return Vec::new(),
+        };
+        let mut out = Vec::new();
+        if curr_node.get_freq() > 0 {
+            out.push((suffix.to_string(), curr_node.get_freq()));
+        }
+        out.extend(curr_node.words(reversed_suffix).map(|(word, freq)| (reverse(&word), freq)));
+        out
+    }
+
+    // The longest ending the word shares with any other word that keep accepts, and every such
+    // word. Backs off one letter at a time from the deepest node the word reaches
+    pub fn longest_common_suffix(&self, word: &str, keep: impl Fn(&str) -> bool) -> (String, Vec<(String, u32)>) {
+        let letters: Vec<char> = word.chars().rev().collect();
+        let mut depth = 0;
+        let mut curr_node = self.reversed.get_root();
+        while let Some(next_node) = letters.get(depth).and_then(|letter| curr_node.get_child(*letter)) {
+            curr_node = next_node;
+            depth += 1;
+        }
+
+        while depth > 0 {
+            let suffix: String = letters[..depth].iter().rev().collect();
+            let words: Vec<(String, u32)> = self.ends_with(&suffix).into_iter()
+                .filter(|(other, _)| other != word && keep(other))
+                .collect();
+            if !words.is_empty() {
+                return (suffix, words);
+            }
+            depth -= 1;
+        }
+        (String::new(), Vec::new())
+    }
+}
+
+fn reverse(word: &str) -> String {
+    word.chars().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> SuffixIndex {
+        let mut index = SuffixIndex::new();
+        for (word, freq) in [("nation", 3), ("station", 2), ("motion", 5), ("ion", 1), ("lion", 4), ("cat", 2), ("café", 1)] {
+            index.add(word, freq);
+        }
+        index
+    }
+
+    #[test]
+    fn test_ends_with() {
+        let index = setup();
+        let mut words = index.ends_with("tion");
+        words.sort();
+        assert_eq!(vec![("motion".to_string(), 5), ("nation".to_string(), 3), ("station".to_string(), 2)], words, "Incorrect words for a suffix");
+
+        let mut words = index.ends_with("ion");
+        words.sort();
+        assert_eq!(5, words.len(), "Suffix that is itself a word not included");
+        assert_eq!(vec![("café".to_string(), 1)], index.ends_with("é"), "Accented suffix not found");
+        assert!(index.ends_with("dog").is_empty(), "Found words for a missing suffix");
+        assert_eq!(7, index.ends_with("").len(), "Empty suffix did not match every word");
+    }
+
+    #[test]
+    fn test_longest_common_suffix() {
+        let index = setup();
+        let (suffix, mut words) = index.longest_common_suffix("ration", |_| true);
+        words.sort();
+        assert_eq!("ation", suffix, "Incorrect longest common suffix");
+        assert_eq!(vec![("nation".to_string(), 3), ("station".to_string(), 2)], words, "Incorrect words sharing the suffix");
+
+        let (suffix, words) = index.longest_common_suffix("nation", |_| true);
+        assert_eq!(("ation".to_string(), vec![("station".to_string(), 2)]), (suffix, words), "Word matched itself");
+
+        let (suffix, _) = index.longest_common_suffix("ration", |word| word != "nation" && word != "station");
+        assert_eq!("tion", suffix, "Rejected words not skipped");
+        assert_eq!((String::new(), Vec::new()), index.longest_common_suffix("xyz", |_| true), "Found a suffix shared with nothing");
+    }
+}
@@ -0,0 +1,124 @@
+use super::distance;
+use std::collections::{HashMap, HashSet};
+
+// Symmetric delete index: every dictionary word is stored under each string reachable by
+// deleting up to max_distance of its letters, so a query only has to generate its own
+// deletes instead of every insertion, substitution and transposition
+#[derive(Debug, Clone)]
+pub struct DeleteIndex {
+    max_distance: u32,
+    words: Vec<String>,
+    deletes: HashMap<String, Vec<u32>>,
+}
+
+impl DeleteIndex {
+    pub fn new(max_distance: u32) -> Self {
+        Self {max_distance, words: Vec::new(), deletes: HashMap::new()}
+    }
+
+    pub fn get_max_distance(&self) -> u32 {
+        self.max_distance
+    }
+
+    pub fn get_word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn add(&mut self, word: &str) {
+        let index = self.words.len() as u32;
+        self.words.push(word.to_string());
+
+        for delete in Self::deletes(word, self.max_distance) {
+            self.deletes.entry(delete).or_default().push(index);
+        }
+    }
+
+    pub fn lookup(&self, word: &str, max_distance: u32) -> Vec<(String, u32)> {
+        let max_distance = max_distance.min(self.max_distance);
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut out = Vec::new();
+
+        for delete in Self::deletes(word, max_distance) {
+            let indices = match self.deletes.get(&delete) {
+                Some(indices) => indices,
+                None => continue,
+            };
+            for index in indices {
+                if !seen.insert(*index) {
+                    continue;
+                }
+                let candidate = &self.words[*index as usize];
+                let distance = distance::damerau_levenshtein(word, candidate);
+                if distance <= max_distance {
+                    out.push((candidate.clone(), distance));
+                }
+            }
+        }
+
+        out
+    }
+
+    fn deletes(word: &str, max_distance: u32) -> HashSet<String> {
+        let mut out: HashSet<String> = HashSet::new();
+        let mut frontier: Vec<Vec<char>> = vec![word.chars().collect()];
+        out.insert(word.to_string());
+
+        for _ in 0..max_distance {
+            let mut next_frontier = Vec::new();
+            for letters in frontier.iter() {
+                for i in 0..letters.len() {
+                    let mut new_word = letters.clone();
+                    new_word.remove(i);
+                    if out.insert(new_word.iter().collect()) {
+                        next_frontier.push(new_word);
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn setup() -> DeleteIndex {
+        let mut index = DeleteIndex::new(2);
+        for word in ["cares", "caress", "baboon", "car", "café"] {
+            index.add(word);
+        }
+        index
+    }
+
+    #[test]
+    fn test_deletes() {
+        let deletes = DeleteIndex::deletes("car", 2);
+        let mut deletes: Vec<&str> = deletes.iter().map(|delete| delete.as_str()).collect();
+        deletes.sort();
+        assert_eq!(vec!["a", "ar", "c", "ca", "car", "cr", "r"], deletes, "Incorrect deletes of \"car\" up to distance two");
+        assert_eq!(1, DeleteIndex::deletes("car", 0).len(), "Deletes at distance zero should only hold the word itself");
+    }
+
+    #[test]
+    fn test_lookup() {
+        let index = setup();
+        assert_eq!(5, index.get_word_count(), "Incorrect word count");
+
+        let mut found = index.lookup("cars", 1);
+        found.sort();
+        assert_eq!(vec![("car".to_string(), 1), ("cares".to_string(), 1)], found, "Incorrect words within distance one of \"cars\"");
+
+        let mut found = index.lookup("acres", 2);
+        found.sort();
+        assert_eq!(vec![("cares".to_string(), 1), ("caress".to_string(), 2)], found, "Incorrect words within distance two of \"acres\"");
+
+        assert_eq!(vec![("café".to_string(), 1)], index.lookup("cafe", 1), "Accented word not found");
+        assert_eq!(vec![("baboon".to_string(), 0)], index.lookup("baboon", 0), "Exact match not found at distance zero");
+        assert!(index.lookup("zzzzzz", 2).is_empty(), "Found words for nonsense query");
+        assert!(index.lookup("cadess", 5).iter().all(|(_, distance)| *distance <= 2), "Lookup exceeded the distance the index was built for");
+    }
+}
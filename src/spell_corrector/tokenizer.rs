@@ -0,0 +1,180 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    word: String,
+    start: usize,
+    end: usize,
+}
+
+impl Token {
+    pub fn get_word(&self) -> &str {
+        &self.word
+    }
+    // Byte offsets into the text, so &text[start..end] is the word as it was written
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+}
+
+// Splits prose into words, leaving out punctuation, numbers, URLs, email addresses and file
+// paths. Apostrophes only count inside a word, and curly ones are straightened
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut out = Vec::new();
+    for (start, chunk) in chunks(text) {
+        if !is_skipped(chunk) {
+            split_words(chunk, start, &mut out);
+        }
+    }
+    out
+}
+
+// Splits a word list on whitespace and only trims the punctuation around each entry, so words
+// like "peut-être" or "1st" that prose tokenizing would split or skip are kept whole. URLs,
+// addresses, paths and plain numbers still aren't words
+pub fn split_entries(text: &str) -> Vec<Token> {
+    let mut out = Vec::new();
+    for (start, chunk) in chunks(text) {
+        let trimmed = chunk.trim_start_matches(|letter: char| !letter.is_alphanumeric());
+        let word = trimmed.trim_end_matches(|letter: char| !letter.is_alphanumeric());
+        if is_skipped(chunk) || !word.chars().any(|letter| letter.is_alphabetic()) {
+            continue;
+        }
+        let word_start = start + chunk.len() - trimmed.len();
+        out.push(Token {word: word.replace('’', "'"), start: word_start, end: word_start + word.len()});
+    }
+    out
+}
+
+// Runs of text between whitespace, with the byte offset of each
+fn chunks(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut chunk_start = None;
+
+    for (index, letter) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (chunk_start, letter.is_whitespace()) {
+            (None, false) => chunk_start = Some(index),
+            (Some(start), true) => {
+                out.push((start, &text[start..index]));
+                chunk_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    out
+}
+
+fn is_skipped(chunk: &str) -> bool {
+    is_url(chunk) || chunk.contains('@') || is_path(chunk)
+}
+
+fn is_url(chunk: &str) -> bool {
+    chunk.contains("://") || chunk.to_lowercase().starts_with("www.")
+}
+
+// A single slash between words reads as "and/or", so a path needs more than that to go on
+fn is_path(chunk: &str) -> bool {
+    if chunk.contains('\\') {
+        return true;
+    }
+    if !chunk.contains('/') {
+        return false;
+    }
+    let last = chunk.trim_end_matches(|letter: char| !letter.is_alphanumeric()).rsplit('/').next().unwrap_or("");
+    chunk.starts_with(['/', '~', '.']) || chunk.matches('/').count() > 1 || last.contains('.')
+}
+
+fn split_words(chunk: &str, offset: usize, out: &mut Vec<Token>) {
+    let letters: Vec<(usize, char)> = chunk.char_indices().collect();
+    let mut i = 0;
+
+    while i < letters.len() {
+        if !letters[i].1.is_alphanumeric() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < letters.len() {
+            let letter = letters[i].1;
+            let inner_apostrophe = (letter == '\'' || letter == '’')
+                && i + 1 < letters.len() && letters[i + 1].1.is_alphanumeric();
+            if !letter.is_alphanumeric() && !inner_apostrophe {
+                break;
+            }
+            i += 1;
+        }
+
+        let end = if i < letters.len() {letters[i].0} else {chunk.len()};
+        let word = &chunk[letters[start].0..end];
+        // Anything with a digit is a number, an ordinal or a code rather than a word
+        if !word.chars().any(|letter| letter.is_numeric()) {
+            out.push(Token {word: word.replace('’', "'"), start: offset + letters[start].0, end: offset + end});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        tokenize(text).iter().map(|token| token.get_word().to_string()).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let text = "Hello, world! It's  \"fine\".";
+        let tokens = tokenize(text);
+
+        assert_eq!(vec!["Hello", "world", "It's", "fine"], words(text), "Punctuation not stripped from words");
+        assert_eq!((0, 5), (tokens[0].get_start(), tokens[0].get_end()), "Incorrect offsets for first word");
+        assert_eq!("world", &text[tokens[1].get_start()..tokens[1].get_end()], "Offsets do not point at the word");
+        assert_eq!("fine", &text[tokens[3].get_start()..tokens[3].get_end()], "Offsets do not point at the quoted word");
+        assert!(tokenize("").is_empty(), "Found words in empty text");
+        assert!(tokenize(" \n\t ").is_empty(), "Found words in blank text");
+    }
+
+    #[test]
+    fn test_apostrophes_and_hyphens() {
+        assert_eq!(vec!["don't", "rock'n'roll"], words("don't rock'n'roll"), "Inner apostrophes not kept");
+        assert_eq!(vec!["quoted", "dogs"], words("'quoted' dogs'"), "Outer apostrophes kept");
+        assert_eq!(vec!["don't"], words("don’t"), "Curly apostrophe not straightened");
+        assert_eq!(vec!["well", "known"], words("well-known"), "Hyphenated words not split");
+        assert_eq!(vec!["and", "or"], words("and/or"), "Words either side of a slash not found");
+    }
+
+    #[test]
+    fn test_split_entries() {
+        let text = "peut-être, 1st «café» rock’n’roll. -- well-known";
+        let entries = split_entries(text);
+
+        assert_eq!(vec!["peut-être", "1st", "café", "rock'n'roll", "well-known"], entries.iter().map(|token| token.get_word()).collect::<Vec<_>>(),
+                   "Entries split, skipped or left with punctuation around them");
+        assert_eq!("café", &text[entries[2].get_start()..entries[2].get_end()], "Offsets do not point at the entry");
+        assert!(split_entries(" -- ... ").is_empty(), "Found entries in punctuation");
+        assert!(split_entries("42 3.14 https://example.com me@example.com ~/notes.txt").is_empty(), "Numbers, URLs, addresses or paths not skipped");
+    }
+
+    #[test]
+    fn test_skipped() {
+        assert_eq!(vec!["pages"], words("42 pages 3.14 1st mp3 -7"), "Numbers not skipped");
+        assert_eq!(vec!["see", "and"], words("see https://example.com/a?b=c and www.example.org."), "URLs not skipped");
+        assert_eq!(vec!["mail"], words("mail me@example.com"), "Email address not skipped");
+        assert_eq!(vec!["open", "or"], words("open ~/notes.txt or src/main.rs /usr/bin C:\\Users ./run ../up a/b/c"), "File paths not skipped");
+    }
+
+    #[test]
+    fn test_unicode_offsets() {
+        let text = "«Zażółć» gęślą, jaźń!";
+        let tokens = tokenize(text);
+
+        assert_eq!(vec!["Zażółć", "gęślą", "jaźń"], words(text), "Accented words not found");
+        for token in tokens.iter() {
+            assert_eq!(token.get_word(), &text[token.get_start()..token.get_end()], "Byte offsets do not match the word");
+        }
+    }
+}
@@ -1,13 +1,25 @@
+use super::distance;
+use super::binary;
+use super::error::SpellError;
+use super::pattern::Pattern;
+use std::cmp::Ordering;
+use std::collections::btree_map;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
 #[derive(Debug)]
 pub struct Node {
     data: char,
     freq: u32,
-    children: Vec<Option<Box<Node>>>,
+    // Highest frequency of any word at or below this node, kept only while the trie's cache is on
+    max_freq: u32,
+    children: BTreeMap<char, Box<Node>>,
 }
 
 impl Node {
     pub fn new(data: char) -> Self {
-        Self {data: data, freq: 0, children: vec![None;26]}
+        Self {data: data, freq: 0, max_freq: 0, children: BTreeMap::new()}
     }
     pub fn get_value(&self) -> char {
         self.data
@@ -16,22 +28,206 @@ impl Node {
     pub fn get_freq(&self) -> u32 {
         self.freq
     }
-    pub fn get_children(&mut self) -> &mut Vec<Option<Box<Node>>> {
+    pub fn get_max_freq(&self) -> u32 {
+        self.max_freq
+    }
+    pub fn get_children(&mut self) -> &mut BTreeMap<char, Box<Node>> {
         &mut self.children
     }
+    pub fn get_child(&self, letter: char) -> Option<&Node> {
+        self.children.get(&letter).map(|child| &**child)
+    }
     pub fn increment_freq(&mut self) {
         self.freq += 1;
     }
+    pub fn set_freq(&mut self, freq: u32) {
+        self.freq = freq;
+    }
 }
 
 impl Default for Node {
     fn default() -> Self {
-        Self {data: '\0', freq: 0, children: vec![None;26]}
+        Self {data: '\0', freq: 0, max_freq: 0, children: BTreeMap::new()}
+    }
+}
+// Leaves out the cached highest frequency, which equality ignores too
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.freq.hash(state);
+        self.children.hash(state);
     }
 }
 impl Clone for Node {
     fn clone(&self) -> Self {
-        Self {data: self.data, freq: self.freq, children: self.children.to_owned() }
+        Self {data: self.data, freq: self.freq, max_freq: self.max_freq, children: self.children.to_owned() }
+    }
+}
+
+// Read-only view of a node, shared by the pointer trie and the flat one in mapped.rs, so
+// lookup, fuzzy search, completion and iteration run unchanged on either
+pub trait TrieCursor: Copy {
+    type Children: Iterator<Item = Self>;
+
+    fn get_value(&self) -> char;
+    fn get_freq(&self) -> u32;
+    fn get_max_freq(&self) -> u32;
+    fn get_child(&self, letter: char) -> Option<Self>;
+    // In letter order
+    fn child_nodes(&self) -> Self::Children;
+
+    // The node the word leads to from this one, whether or not it ends a word
+    fn descend(self, word: &str) -> Option<Self> {
+        let mut curr_node = self;
+        for letter in word.chars() {
+            curr_node = curr_node.get_child(letter)?;
+        }
+        Some(curr_node)
+    }
+
+    // Every word within max_distance of the given one in a single walk down from a root,
+    // computing one Damerau-Levenshtein row per node and abandoning any branch whose best cell
+    // already exceeds max_distance. Nearest first, then alphabetically
+    fn fuzzy_find(self, word: &str, max_distance: u32) -> Vec<(String, u32)> {
+        let letters: Vec<char> = word.chars().collect();
+        let first_row: Vec<u32> = (0..=letters.len() as u32).collect();
+        let mut search = FuzzySearch {letters: &letters, max_distance, holder: String::new(), rows: vec![first_row], last_rows: HashMap::new(), out: Vec::new()};
+
+        for next_node in self.child_nodes() {
+            search.walk(next_node);
+        }
+
+        search.out.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        search.out
+    }
+
+    // Every word below a root that matches the pattern, in lexicographic order
+    fn find_pattern(self, pattern: &Pattern) -> Vec<(String, u32)> {
+        let mut out = Vec::new();
+        walk_pattern(self, &pattern.start(), pattern, &mut String::new(), &mut out);
+        out
+    }
+
+    // Words below a root spelled from the bag of letters, each used no more often than it appears
+    // there, and all of them unless partial. Most frequent first, then alphabetically
+    fn anagrams(self, letters: &str, partial: bool) -> Vec<(String, u32)> {
+        let mut counts: BTreeMap<char, u32> = BTreeMap::new();
+        for letter in letters.chars() {
+            *counts.entry(letter).or_default() += 1;
+        }
+        let mut out = Vec::new();
+        walk_anagrams(self, &mut counts, letters.chars().count(), partial, &mut String::new(), &mut out);
+        out.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        out
+    }
+
+    // The k most frequent words at or below this node, which prefix spells out, most frequent
+    // first, then alphabetically. Best-first only works while get_max_freq is up to date
+    fn complete(self, prefix: String, k: usize, best_first: bool) -> Vec<(String, u32)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        if !best_first {
+            let mut out = Vec::new();
+            if self.get_freq() > 0 {
+                out.push((prefix.clone(), self.get_freq()));
+            }
+            out.extend(self.words(prefix));
+            out.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            out.truncate(k);
+            return out;
+        }
+
+        // A node is queued at the highest frequency below it, so every word that should come
+        // before the next one popped has already been reached
+        let mut out = Vec::new();
+        let mut queue = BinaryHeap::new();
+        queue.push(Completion {freq: self.get_max_freq(), word: prefix, node: Some(self)});
+
+        while let Some(entry) = queue.pop() {
+            match entry.node {
+                None => {
+                    out.push((entry.word, entry.freq));
+                    if out.len() == k {
+                        break;
+                    }
+                }
+                Some(node) => {
+                    if node.get_freq() > 0 {
+                        queue.push(Completion {freq: node.get_freq(), word: entry.word.clone(), node: None});
+                    }
+                    for next_node in node.child_nodes() {
+                        let mut word = entry.word.clone();
+                        word.push(next_node.get_value());
+                        queue.push(Completion {freq: next_node.get_max_freq(), word, node: Some(next_node)});
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    // Every word below this node, which prefix spells out, in lexicographic order
+    fn words(self, prefix: String) -> Words<Self> {
+        Words {stack: vec![self.child_nodes()], holder: prefix}
+    }
+}
+
+fn walk_pattern<N: TrieCursor>(curr_node: N, positions: &[usize], pattern: &Pattern, holder: &mut String, out: &mut Vec<(String, u32)>) {
+    for next_node in curr_node.child_nodes() {
+        let next_positions = pattern.step(positions, next_node.get_value());
+        if next_positions.is_empty() {
+            continue;
+        }
+        holder.push(next_node.get_value());
+        if next_node.get_freq() > 0 && pattern.is_match(&next_positions) {
+            out.push((holder.clone(), next_node.get_freq()));
+        }
+        walk_pattern(next_node, &next_positions, pattern, holder, out);
+        holder.pop();
+    }
+}
+
+// Only follows the letters still left in the bag, so branches that need any other are never
+// visited
+fn walk_anagrams<N: TrieCursor>(curr_node: N, counts: &mut BTreeMap<char, u32>, remaining: usize, partial: bool, holder: &mut String, out: &mut Vec<(String, u32)>) {
+    let letters: Vec<char> = counts.iter().filter(|(_, count)| **count > 0).map(|(letter, _)| *letter).collect();
+    for letter in letters {
+        let next_node = match curr_node.get_child(letter) {
+            Some(next_node) => next_node,
+            None => continue,
+        };
+        *counts.get_mut(&letter).unwrap() -= 1;
+        holder.push(letter);
+        if next_node.get_freq() > 0 && (partial || remaining == 1) {
+            out.push((holder.clone(), next_node.get_freq()));
+        }
+        walk_anagrams(next_node, counts, remaining - 1, partial, holder, out);
+        holder.pop();
+        *counts.get_mut(&letter).unwrap() += 1;
+    }
+}
+
+impl<'a> TrieCursor for &'a Node {
+    type Children = std::iter::Map<btree_map::Values<'a, char, Box<Node>>, fn(&'a Box<Node>) -> &'a Node>;
+
+    fn get_value(&self) -> char {
+        self.data
+    }
+    fn get_freq(&self) -> u32 {
+        self.freq
+    }
+    fn get_max_freq(&self) -> u32 {
+        self.max_freq
+    }
+    fn get_child(&self, letter: char) -> Option<Self> {
+        self.children.get(&letter).map(|child| &**child)
+    }
+    fn child_nodes(&self) -> Self::Children {
+        let unbox: fn(&'a Box<Node>) -> &'a Node = |child| child;
+        self.children.values().map(unbox)
     }
 }
 
@@ -39,136 +235,397 @@ impl Clone for Node {
 pub struct Trie {
     root: Box<Node>,
     num_nodes: u32,
-    num_words: u32
+    num_words: u32,
+    max_freq_cache: bool,
 }
 
 impl Default for Trie {
     fn default() -> Self {
-        Self {root: Default::default(), num_nodes: 1, num_words: 0}
+        Self {root: Default::default(), num_nodes: 1, num_words: 0, max_freq_cache: false}
     }
 }
 impl Trie {
     pub fn new() -> Self {
-        Self {root: Default::default(), num_nodes: 1, num_words: 0}
+        Self {root: Default::default(), num_nodes: 1, num_words: 0, max_freq_cache: false}
     }
     pub fn add(&mut self, word: &String) {
         let lower_word = word.to_lowercase();
-        let mut curr_node = &mut self.root;// self.root;
-       
-        let mut i = 0;
-        for letter in lower_word.as_str().chars() {
-            let index :usize = letter.to_digit(36).unwrap() as usize - 10;
-
-            if curr_node.get_children()[index].is_some() {
-                curr_node = curr_node.get_children()[index].as_mut().unwrap();
-            }
-            else {
-                    curr_node.get_children()[index] = Some(Box::new(Node::new(letter)));
-                    curr_node = curr_node.get_children()[index].as_mut().unwrap(); 
-                    self.num_nodes += 1;
-            }
-            if curr_node.get_freq() < 1 && i == lower_word.len() -1 {
-                self.num_words += 1;
-                curr_node.increment_freq();
-            }
-            else if curr_node.get_freq() > 0 && i == lower_word.len() -1 {
-                curr_node.increment_freq();
+        if lower_word.is_empty() {
+            return;
+        }
+        let curr_node = self.node_for(&lower_word);
+        let new_word = curr_node.get_freq() < 1;
+        curr_node.increment_freq();
+        let freq = curr_node.get_freq();
+        if new_word {
+            self.num_words += 1;
+        }
+
+        if self.max_freq_cache {
+            let mut curr_node = &mut self.root;
+            curr_node.max_freq = curr_node.max_freq.max(freq);
+            for letter in lower_word.chars() {
+                curr_node = curr_node.get_children().get_mut(&letter).unwrap();
+                curr_node.max_freq = curr_node.max_freq.max(freq);
             }
-            i += 1;
         }
     }
 
-    pub fn find(&mut self, word: &String) -> Option<&Node> {
-        let lower_word = word.as_str().to_lowercase();
+    // The word's node, adding any nodes missing on the way
+    fn node_for(&mut self, lower_word: &str) -> &mut Node {
         let mut curr_node = &mut self.root;
-
         for letter in lower_word.chars() {
-            let index :usize = letter.to_digit(36).unwrap() as usize - 10;
-            match &mut curr_node.get_children()[index] {
-                Some(next_node) => {
-                    curr_node =  next_node; 
-                }
-                None => {
-                    return None;
-                }
+            curr_node = curr_node.get_children().entry(letter).or_insert_with(|| {
+                self.num_nodes += 1;
+                Box::new(Node::new(letter))
+            });
+        }
+        curr_node
+    }
+
+    // Keeps every node's max_freq up to date so complete can go straight to the most frequent
+    // words, at the cost of extra work whenever a frequency changes
+    pub fn set_max_freq_cache(&mut self, enabled: bool) {
+        if enabled && !self.max_freq_cache {
+            Trie::compute_max_freq(&mut self.root);
+        }
+        self.max_freq_cache = enabled;
+    }
+    pub fn has_max_freq_cache(&self) -> bool {
+        self.max_freq_cache
+    }
+
+    fn compute_max_freq(curr_node: &mut Node) -> u32 {
+        let mut max_freq = curr_node.get_freq();
+        for next_node in curr_node.children.values_mut() {
+            max_freq = max_freq.max(Trie::compute_max_freq(next_node));
+        }
+        curr_node.max_freq = max_freq;
+        max_freq
+    }
+
+    // Recomputes max_freq along the word's path, from the deepest node up
+    fn refresh_max_freq(curr_node: &mut Node, letters: &[char]) {
+        if let Some((letter, rest)) = letters.split_first() {
+            if let Some(next_node) = curr_node.children.get_mut(letter) {
+                Trie::refresh_max_freq(next_node, rest);
             }
         }
+        curr_node.max_freq = curr_node.children.values().map(|next_node| next_node.max_freq).fold(curr_node.freq, u32::max);
+    }
+
+    // Takes the word out entirely, returning how often it had been added
+    pub fn remove(&mut self, word: &String) -> u32 {
+        let letters: Vec<char> = word.to_lowercase().chars().collect();
+        if letters.is_empty() {
+            return 0;
+        }
 
-        if curr_node.get_freq() >= 1 {
-            return Some(&*curr_node);
+        let (old_freq, pruned) = Trie::remove_helper(&mut self.root, &letters);
+        if old_freq > 0 {
+            self.num_words -= 1;
         }
+        self.num_nodes -= pruned;
+        if self.max_freq_cache {
+            Trie::refresh_max_freq(&mut self.root, &letters);
+        }
+        old_freq
+    }
 
-        return None;
+    // Undoes one add, returning the frequency left
+    pub fn decrement(&mut self, word: &String) -> u32 {
+        let freq = match self.find(word) {
+            Some(node) => node.get_freq(),
+            None => return 0,
+        };
+        self.set_freq(word, freq - 1);
+        freq - 1
     }
 
+    pub fn set_freq(&mut self, word: &String, freq: u32) {
+        if freq == 0 {
+            self.remove(word);
+            return;
+        }
+        let lower_word = word.to_lowercase();
+        if lower_word.is_empty() {
+            return;
+        }
+        let curr_node = self.node_for(&lower_word);
+        let new_word = curr_node.get_freq() < 1;
+        curr_node.set_freq(freq);
+        if new_word {
+            self.num_words += 1;
+        }
+
+        if self.max_freq_cache {
+            let letters: Vec<char> = lower_word.chars().collect();
+            Trie::refresh_max_freq(&mut self.root, &letters);
+        }
+    }
+
+    // Clears the word below curr_node and prunes every node left with neither a word nor
+    // children. Returns the word's old frequency and the number of nodes pruned
+    fn remove_helper(curr_node: &mut Node, letters: &[char]) -> (u32, u32) {
+        if letters.is_empty() {
+            let old_freq = curr_node.get_freq();
+            curr_node.set_freq(0);
+            return (old_freq, 0);
+        }
+
+        let next_node = match curr_node.children.get_mut(&letters[0]) {
+            Some(next_node) => next_node,
+            None => return (0, 0),
+        };
+        let (old_freq, mut pruned) = Trie::remove_helper(next_node, &letters[1..]);
+        if next_node.get_freq() == 0 && next_node.children.is_empty() {
+            curr_node.children.remove(&letters[0]);
+            pruned += 1;
+        }
+        (old_freq, pruned)
+    }
+
+    pub fn find(&self, word: &String) -> Option<&Node> {
+        let lower_word = word.as_str().to_lowercase();
+        TrieCursor::descend(&*self.root, &lower_word).filter(|node| node.get_freq() >= 1)
+    }
+
+    // Every word within k edits of the given one, with its distance
+    pub fn fuzzy_find(&self, word: &String, k: u32) -> Vec<(String, u32)> {
+        TrieCursor::fuzzy_find(&*self.root, &word.to_lowercase(), k)
+    }
+
+    // Words matching a pattern of letters, `?` for any one letter, `*` for any run of them and
+    // `[...]` for a class like `[aeiou]`, `[a-m]` or `[^aeiou]`
+    pub fn find_pattern(&self, pattern: &str) -> Result<Vec<(String, u32)>, SpellError> {
+        let pattern = Pattern::parse(pattern)?;
+        Ok(TrieCursor::find_pattern(&*self.root, &pattern))
+    }
+
+    // Words using exactly the given letters, most frequent first
+    pub fn anagrams(&self, letters: &String) -> Vec<(String, u32)> {
+        TrieCursor::anagrams(&*self.root, &letters.to_lowercase(), false)
+    }
+    // Words using some of the given letters, most frequent first
+    pub fn sub_anagrams(&self, letters: &String) -> Vec<(String, u32)> {
+        TrieCursor::anagrams(&*self.root, &letters.to_lowercase(), true)
+    }
+
+    // The k most frequent words starting with prefix, most frequent first, then alphabetically
+    pub fn complete(&self, prefix: &String, k: usize) -> Vec<(String, u32)> {
+        let lower_prefix = prefix.to_lowercase();
+        match TrieCursor::descend(&*self.root, &lower_prefix) {
+            Some(curr_node) => curr_node.complete(lower_prefix, k, self.max_freq_cache),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_root(&self) -> &Node {
+        &self.root
+    }
     pub fn get_word_count(&self) -> u32 {
         self.num_words
     }
     pub fn get_node_count(&self) -> u32 {
         self.num_nodes
     }
-    fn to_string_helper(mut curr_node: Node, holder: &mut String, out: &mut String) {
-        for i in 0..25 {
-            
-            match &curr_node.get_children()[i] {
+    // Every word with its frequency, in lexicographic order, borrowing the trie
+    pub fn iter(&self) -> Words<&Node> {
+        TrieCursor::words(&*self.root, String::new())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::encode(self.iter())
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Trie, SpellError> {
+        Ok(binary::decode(bytes)?.into_iter().collect())
+    }
+
+    // Identifies the words and frequencies the same way across runs and builds, which Hash
+    // doesn't promise, so it can key a cache of a dictionary compiled from this one
+    pub fn digest(&self) -> u64 {
+        binary::digest(self.iter())
+    }
+    // Walks both tries in step, letters in order, so a child either side lacks shows up as a
+    // mismatch instead of being skipped
+    fn compare(base_node: &Node, test_node: &Node) -> bool {
+        base_node.freq == test_node.freq
+            && base_node.children.len() == test_node.children.len()
+            && base_node.children.iter().zip(test_node.children.iter())
+                .all(|((base_letter, base_child), (test_letter, test_child))| base_letter == test_letter && Trie::compare(base_child, test_child))
+    }
+}
+// Depth-first over the children still to visit at each level, so words come out in order and
+// only the current one is ever built
+pub struct Words<N: TrieCursor> {
+    stack: Vec<N::Children>,
+    holder: String,
+}
+
+impl<N: TrieCursor> Iterator for Words<N> {
+    type Item = (String, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
                 Some(next_node) => {
-                    holder.push(next_node.get_value());
+                    self.holder.push(next_node.get_value());
+                    self.stack.push(next_node.child_nodes());
                     if next_node.get_freq() > 0 {
-                        let output = holder.as_str().to_owned() + "\n";
-                        *out += output.as_str();
-                        //print!("{}",out);
+                        return Some((self.holder.clone(), next_node.get_freq()));
                     }
-                    Trie::to_string_helper(*next_node.clone(), holder, out);
-                    holder.pop();
                 }
                 None => {
-                    continue;
+                    self.stack.pop();
+                    self.holder.pop();
                 }
             }
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        let mut out = String::new();
-        let mut holder = String::new();
-        
-        Self::to_string_helper(*self.root.clone(),&mut holder,&mut out);
-        out.pop(); 
+impl<'a> IntoIterator for &'a Trie {
+    type Item = (String, u32);
+    type IntoIter = Words<&'a Node>;
 
-        out
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
-    
-    pub fn hash_code(&mut self) -> i32 {
-        let mut sum :i32 = 0;
-        for i in 0..25 {
-            if self.root.get_children()[i].is_some() {
-                sum += i as i32;
-            }
+}
+
+impl Extend<String> for Trie {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        for word in iter {
+            self.add(&word);
         }
+    }
+}
 
-        sum | self.num_nodes as i32 & self.num_words as i32
+impl<'a> Extend<&'a str> for Trie {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|word| word.to_string()));
     }
-    fn compare(mut base_node: Node, mut test_node: Node) -> bool {
-        if base_node.get_freq() != test_node.get_freq() {
-            return false;
+}
+
+// Adds each frequency to whatever the word already has, so iter and collect round-trip
+impl Extend<(String, u32)> for Trie {
+    fn extend<I: IntoIterator<Item = (String, u32)>>(&mut self, iter: I) {
+        for (word, freq) in iter {
+            let old_freq = self.find(&word).map_or(0, |node| node.get_freq());
+            self.set_freq(&word, old_freq + freq);
         }
+    }
+}
+
+impl FromIterator<String> for Trie {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(iter);
+        trie
+    }
+}
 
-        for i in 0..25 {
-            if base_node.get_children()[i].is_some() && test_node.get_children()[i].is_some() {
+impl<'a> FromIterator<&'a str> for Trie {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(iter);
+        trie
+    }
+}
 
-                if base_node.get_freq() != test_node.get_freq() {
-                    return false;
-                }
-                else if !Trie::compare(*base_node.get_children()[i].as_ref().unwrap().clone(), *test_node.get_children()[i].as_ref().unwrap().clone()) {
-                    return false;
-                }
+impl FromIterator<(String, u32)> for Trie {
+    fn from_iter<I: IntoIterator<Item = (String, u32)>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+// One word per line, in lexicographic order
+impl fmt::Display for Trie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (word, _)) in self.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
             }
+            write!(f, "{}", word)?;
         }
-        true
+        Ok(())
     }
 }
+
+// A queued word, or a node standing in for every word below it
+struct Completion<N> {
+    freq: u32,
+    word: String,
+    node: Option<N>,
+}
+
+impl<N> Ord for Completion<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.freq.cmp(&other.freq).then(other.word.cmp(&self.word))
+    }
+}
+
+impl<N> PartialOrd for Completion<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> PartialEq for Completion<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for Completion<N> {}
+
+struct FuzzySearch<'a> {
+    letters: &'a [char],
+    max_distance: u32,
+    holder: String,
+    rows: Vec<Vec<u32>>,
+    last_rows: HashMap<char, usize>,
+    out: Vec<(String, u32)>,
+}
+
+impl<'a> FuzzySearch<'a> {
+    fn walk<N: TrieCursor>(&mut self, curr_node: N) {
+        let letter = curr_node.get_value();
+        let row = distance::next_row(&self.rows, &self.last_rows, letter, self.letters);
+
+        self.holder.push(letter);
+        if curr_node.get_freq() > 0 && row[self.letters.len()] <= self.max_distance {
+            self.out.push((self.holder.clone(), row[self.letters.len()]));
+        }
+        // No cell of a deeper row can be smaller than this row's minimum
+        if *row.iter().min().unwrap() <= self.max_distance {
+            self.rows.push(row);
+            let prev_last_row = self.last_rows.insert(letter, self.rows.len() - 1);
+            for next_node in curr_node.child_nodes() {
+                self.walk(next_node);
+            }
+            match prev_last_row {
+                Some(last_row) => self.last_rows.insert(letter, last_row),
+                None => self.last_rows.remove(&letter),
+            };
+            self.rows.pop();
+        }
+        self.holder.pop();
+    }
+}
+
 impl Eq for Trie {}
 
+impl Hash for Trie {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.num_words.hash(state);
+        self.num_nodes.hash(state);
+        self.root.hash(state);
+    }
+}
+
 impl PartialEq for Trie {
     fn eq(&self, other: &Self) -> bool {
         if self.num_words != other.num_words {
@@ -178,7 +635,7 @@ impl PartialEq for Trie {
             return false;
         }
 
-        Trie::compare(*self.root.clone(), *other.root.clone())
+        Trie::compare(&self.root, &other.root)
     }
 }
 
@@ -189,9 +646,18 @@ impl PartialEq for Trie {
 mod tests {
 
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
     use std::fs;
    
     
+    fn hash(trie: &Trie) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        trie.hash(&mut hasher);
+        hasher.finish()
+    }
+
     fn setup() -> (Trie, Trie) {
         let trie1 = Trie::new();
         let trie2 = Trie::new();
@@ -316,6 +782,27 @@ mod tests {
         assert_ne!(trie2,trie1, "Two un-equal branching tries found equal.");
     }
 
+    #[test]
+    fn test_asymmetric_equals() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        let trie2 = &mut pair.1;
+
+        trie1.extend(["ca", "z"]);
+        trie2.extend(["ca", "y"]);
+        assert_ne!(trie1, trie2, "Tries differing only in a 'z' branch found equal");
+        assert_ne!(trie2, trie1, "Tries differing only in a 'z' branch found equal");
+
+        let trie3: Trie = ["cat", "dog"].into_iter().collect();
+        let trie4: Trie = ["cab", "dog"].into_iter().collect();
+        assert_ne!(trie3, trie4, "Tries with different children at a deeper level found equal");
+        assert_ne!(trie4, trie3, "Tries with different children at a deeper level found equal");
+
+        let trie5: Trie = ["ab", "c"].into_iter().collect();
+        let trie6: Trie = ["a", "bc"].into_iter().collect();
+        assert_ne!(trie5, trie6, "Tries with the same letters in different places found equal");
+    }
+
     #[test]
     fn test_duplicate_nodes() {
         let mut pair = setup();
@@ -353,7 +840,7 @@ mod tests {
     }
 
     #[test]
-    fn test_hash_code() {
+    fn test_hash() {
         let mut pair = setup();
         let trie1 = &mut pair.0;
         let trie2 = &mut pair.1;
@@ -371,12 +858,12 @@ mod tests {
         trie2.add(&"car".to_string());
         trie2.add(&"car".to_string());
 
-        assert_eq!(trie1.hash_code(), trie1.hash_code(), "Same Trie does not return the same hash code");
-        assert_eq!(trie2.hash_code(), trie1.hash_code(), "Equal Trie object return uneqaul hash codes");
+        assert_eq!(hash(trie1), hash(trie1), "Same Trie does not return the same hash code");
+        assert_eq!(hash(trie2), hash(trie1), "Equal Trie object return uneqaul hash codes");
     }
 
     #[test]
-    fn test_more_hash_code() {
+    fn test_more_hash() {
         let mut pair = setup();
         let trie1 = &mut pair.0;
         let trie2 = &mut pair.1;
@@ -384,21 +871,58 @@ mod tests {
 
         trie1.add(&"dat".to_string());
         trie2.add(&"far".to_string());
-        assert_ne!(trie2.hash_code(), trie1.hash_code(), "The hash code is too simple. Different Tries return same hash code");
+        assert_ne!(hash(trie2), hash(trie1), "The hash code is too simple. Different Tries return same hash code");
 
 
         trie2.add(&"dat".to_string());
         trie1.add(&"far".to_string());
-        assert_eq!(trie2.hash_code(),trie1.hash_code(), "Equal Tries of different construction history return different hash code");
+        assert_eq!(hash(trie2),hash(trie1), "Equal Tries of different construction history return different hash code");
 
         trie2.add(&"da".to_string());
-        assert_ne!(trie2.hash_code(),trie1.hash_code(), "Tries of differing word cound return same hash code.");
+        assert_ne!(hash(trie2),hash(trie1), "Tries of differing word cound return same hash code.");
 
         trie1.add(&"date".to_string());
-        assert_ne!(trie2.hash_code(), trie1.hash_code(), "Tries of differing node count return same hash code");
+        assert_ne!(hash(trie2), hash(trie1), "Tries of differing node count return same hash code");
         
         trie1.add(&"d".to_string());
-        assert_ne!(trie2.hash_code(), trie1.hash_code(), "Different tries of same node count and word count return same hash code");
+        assert_ne!(hash(trie2), hash(trie1), "Different tries of same node count and word count return same hash code");
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        let trie2 = &mut pair.1;
+
+        trie1.extend(["dat", "far"]);
+        trie2.extend(["far", "dat"]);
+        let mut tries = HashSet::new();
+        tries.insert(trie1.clone());
+        assert!(!tries.insert(trie2.clone()), "Equal trie added to a set twice");
+
+        trie2.add(&"dat".to_string());
+        assert_ne!(hash(trie1), hash(trie2), "Tries differing only in frequency return same hash");
+        assert!(tries.insert(trie2.clone()), "Trie differing in frequency not added to a set");
+
+        let mut cached = trie1.clone();
+        cached.set_max_freq_cache(true);
+        assert_eq!(hash(trie1), hash(&cached), "Completion cache changed the hash");
+    }
+
+    #[test]
+    fn test_digest() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        let trie2 = &mut pair.1;
+
+        assert_eq!(binary::digest(Vec::new()), trie1.digest(), "Incorrect digest of an empty trie");
+        trie1.extend(["dat", "far", "dat"]);
+        trie2.extend(["far", "dat"]);
+        assert_ne!(trie1.digest(), trie2.digest(), "Tries differing only in frequency return same digest");
+        trie2.add(&"dat".to_string());
+        assert_eq!(trie1.digest(), trie2.digest(), "Equal tries of different construction history return different digests");
+        assert_eq!(trie1.digest(), Trie::from_bytes(&trie1.to_bytes()).unwrap().digest(), "Digest changed in a binary round trip");
+        assert_eq!(binary::digest(vec![("dat".to_string(), 2), ("far".to_string(), 1)]), trie1.digest(), "Digest not taken over the words in order");
     }
 
     #[test]
@@ -432,6 +956,318 @@ mod tests {
         assert_eq!(trie1.to_string().to_lowercase() == trie2.to_string().to_lowercase(),true, "Equal Trie objects' to_string() methods return different Strings");
     }
 
+    #[test]
+    fn test_iter() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for word in ["zebra", "cares", "caress", "baboon", "car", "car", "fizz"] {
+            trie1.add(&word.to_string());
+        }
+        let words: Vec<(String, u32)> = trie1.iter().collect();
+        assert_eq!(vec![("baboon".to_string(), 1), ("car".to_string(), 2), ("cares".to_string(), 1), ("caress".to_string(), 1), ("fizz".to_string(), 1), ("zebra".to_string(), 1)],
+                   words, "Words not listed in lexicographic order with their frequencies");
+        assert_eq!(trie1.get_word_count() as usize, trie1.into_iter().count(), "Iterator length differs from word count");
+        assert_eq!("baboon\ncar\ncares\ncaress\nfizz\nzebra", trie1.to_string(), "Display lost words containing 'z'");
+
+        let filtered: Vec<String> = trie1.iter().filter(|(_, freq)| *freq > 1).map(|(word, _)| word).collect();
+        assert_eq!(vec!["car"], filtered, "Incorrect filtered words");
+
+        assert_eq!(None, Trie::new().iter().next(), "Found a word in an empty trie");
+        assert_eq!("", Trie::new().to_string(), "Empty trie not displayed as empty");
+    }
+
+    #[test]
+    fn test_collect_and_extend() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for word in ["cares", "caress", "baboon", "car", "car"] {
+            trie1.add(&word.to_string());
+        }
+
+        let trie2: Trie = ["cares", "caress", "baboon", "car", "car"].into_iter().collect();
+        assert_eq!(*trie1, trie2, "Trie collected from words differs from one built with add");
+        let trie3: Trie = trie1.iter().collect();
+        assert_eq!(*trie1, trie3, "Trie collected from its own iterator differs");
+        assert_eq!(trie1.get_node_count(), trie3.get_node_count(), "Collected trie has a different node count");
+
+        let mut trie4: Trie = vec!["car".to_string()].into_iter().collect();
+        trie4.extend(vec!["car".to_string(), "baboon".to_string()]);
+        trie4.extend(["cares"]);
+        trie4.extend(vec![("caress".to_string(), 1), ("zero".to_string(), 0)]);
+        assert_eq!(*trie1, trie4, "Extended trie differs from one built with add");
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for word in ["cares", "caress", "baboon", "car", "car", "café"] {
+            trie1.add(&word.to_string());
+        }
+        let trie2 = Trie::from_bytes(&trie1.to_bytes()).expect("Unable to read back the trie");
+        assert_eq!(*trie1, trie2, "Trie changed in the round trip");
+        assert_eq!(trie1.get_node_count(), trie2.get_node_count(), "Node count changed in the round trip");
+        assert!(Trie::from_bytes(b"baboon").is_err(), "Text read as a binary trie");
+    }
+
+    #[test]
+    fn test_unicode_words() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        trie1.add(&"don't".to_string());
+        trie1.add(&"café".to_string());
+        trie1.add(&"naïve".to_string());
+        trie1.add(&"1st".to_string());
+        trie1.add(&"Straße".to_string());
+        trie1.add(&"zażółć".to_string());
+
+        assert!(trie1.find(&"don't".to_string()).is_some(), "Word with apostrophe not found (\"don't\")");
+        assert!(trie1.find(&"CAFÉ".to_string()).is_some(), "Upper case accented word not found (\"CAFÉ\")");
+        assert!(trie1.find(&"naïve".to_string()).is_some(), "Word with diaeresis not found (\"naïve\")");
+        assert!(trie1.find(&"1st".to_string()).is_some(), "Word with digit not found (\"1st\")");
+        assert!(trie1.find(&"straße".to_string()).is_some(), "Word with sharp s not found (\"straße\")");
+        assert!(trie1.find(&"zażółć".to_string()).is_some(), "Polish word not found (\"zażółć\")");
+        assert!(trie1.find(&"cafe".to_string()).is_none(), "Found unaccented \"cafe\" (should have returned None)");
+        assert_eq!(6, trie1.get_word_count(), "Incorrect word count after unicode adds");
+        assert_eq!(30, trie1.get_node_count(), "Incorrect node count after unicode adds");
+    }
+
+    #[test]
+    fn test_empty_word() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        trie1.add(&"".to_string());
+        assert_eq!(0, trie1.get_word_count(), "Empty word was counted as a word");
+        assert_eq!(1, trie1.get_node_count(), "Empty word added nodes");
+        assert!(trie1.find(&"".to_string()).is_none(), "Found empty word (should have returned None)");
+    }
+
+    #[test]
+    fn test_fuzzy_find() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        trie1.add(&"cares".to_string());
+        trie1.add(&"caress".to_string());
+        trie1.add(&"baboon".to_string());
+        trie1.add(&"car".to_string());
+
+        let mut found = trie1.fuzzy_find(&"cars".to_string(), 1);
+        found.sort();
+        assert_eq!(vec![("car".to_string(), 1), ("cares".to_string(), 1)], found, "Incorrect words within distance one of \"cars\"");
+
+        let mut found = trie1.fuzzy_find(&"acres".to_string(), 2);
+        found.sort();
+        assert_eq!(vec![("cares".to_string(), 1), ("caress".to_string(), 2)], found, "Incorrect words within distance two of \"acres\" (transposition counts as one edit)");
+
+        let mut found = trie1.fuzzy_find(&"acr".to_string(), 2);
+        found.sort();
+        assert_eq!(vec![("car".to_string(), 1)], found, "Incorrect words within distance two of \"acr\"");
+
+        assert_eq!(vec![("baboon".to_string(), 0)], trie1.fuzzy_find(&"BABOON".to_string(), 0), "Exact match not found at distance zero");
+        assert!(trie1.fuzzy_find(&"zzzzzz".to_string(), 3).is_empty(), "Found words for nonsense query");
+        assert_eq!(vec![("car".to_string(), 3)], trie1.fuzzy_find(&"".to_string(), 3), "Empty query should match words no longer than the distance");
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for word in ["cares", "caress", "baboon", "car", "car", "cart", "scar", "café", "cat"] {
+            trie1.add(&word.to_string());
+        }
+        let words = |pattern: &str| -> Vec<String> {
+            trie1.find_pattern(pattern).unwrap().into_iter().map(|(word, _)| word).collect()
+        };
+
+        assert_eq!(vec![("car".to_string(), 2), ("cat".to_string(), 1)], trie1.find_pattern("ca?").unwrap(), "Incorrect matches with frequencies for \"ca?\"");
+        assert_eq!(vec!["car", "cares", "caress", "cart", "scar"], words("*car*"), "Incorrect matches for \"*car*\"");
+        assert_eq!(vec!["cares", "caress"], words("CAR?S*"), "Incorrect matches for \"CAR?S*\"");
+        assert_eq!(vec!["cat"], words("ca[^r-s]"), "Incorrect matches for a negated class");
+        assert_eq!(vec!["café"], words("caf?"), "Question mark did not match an accented letter");
+        assert_eq!(vec!["baboon"], words("[a-b]*[n]"), "Incorrect matches for a range");
+        assert_eq!(trie1.get_word_count() as usize, words("*").len(), "Star alone did not match every word");
+        assert!(words("c").is_empty(), "Matched a prefix that isn't a word");
+        assert!(matches!(trie1.find_pattern("ca[r"), Err(SpellError::InvalidPattern(_))), "Invalid pattern accepted");
+    }
+
+    #[test]
+    fn test_anagrams() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for (word, freq) in [("listen", 2), ("silent", 5), ("enlist", 1), ("tinsel", 1), ("list", 3), ("lent", 1), ("tin", 4), ("lists", 9), ("é", 1)] {
+            trie1.set_freq(&word.to_string(), freq);
+        }
+
+        assert_eq!(vec![("silent".to_string(), 5), ("listen".to_string(), 2), ("enlist".to_string(), 1), ("tinsel".to_string(), 1)],
+                   trie1.anagrams(&"SILENT".to_string()), "Incorrect anagrams, or not ranked by frequency");
+        assert_eq!(vec![("silent".to_string(), 5), ("tin".to_string(), 4), ("list".to_string(), 3), ("listen".to_string(), 2), ("enlist".to_string(), 1), ("lent".to_string(), 1), ("tinsel".to_string(), 1)],
+                   trie1.sub_anagrams(&"silent".to_string()), "Incorrect sub-anagrams");
+        assert!(!trie1.sub_anagrams(&"silent".to_string()).iter().any(|(word, _)| word == "lists"), "Letter used more often than it appears");
+        assert_eq!(vec![("lists".to_string(), 9)], trie1.anagrams(&"tslsi".to_string()), "Repeated letter not counted");
+        assert_eq!(vec![("é".to_string(), 1)], trie1.anagrams(&"É".to_string()), "Accented letter not matched");
+        assert!(trie1.anagrams(&"".to_string()).is_empty(), "Found anagrams of nothing");
+        assert!(trie1.anagrams(&"silentx".to_string()).is_empty(), "Anagram left a letter unused");
+    }
+
+    #[test]
+    fn test_fuzzy_find_matches_every_word() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for word in ["cares", "caress", "baboon", "car", "cart", "scar", "acre", "café", "a", "ab", "ba"] {
+            trie1.add(&word.to_string());
+        }
+
+        for query in ["cars", "acres", "ab", "cafe", "bab", "x", "carses"] {
+            for k in 0..4 {
+                let mut expected: Vec<(String, u32)> = trie1.iter()
+                    .map(|(word, _)| (word.clone(), distance::damerau_levenshtein(query, &word)))
+                    .filter(|(_, distance)| *distance <= k)
+                    .collect();
+                expected.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+                assert_eq!(expected, trie1.fuzzy_find(&query.to_string(), k), "Walk differs from checking every word for \"{}\" within {}", query, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        let trie2 = &mut pair.1;
+
+        trie1.add(&"cares".to_string());
+        trie1.add(&"caress".to_string());
+        trie1.add(&"baboon".to_string());
+        trie1.add(&"car".to_string());
+        trie1.add(&"car".to_string());
+
+        assert_eq!(2, trie1.remove(&"CAR".to_string()), "Removal did not return the word's frequency");
+        assert!(trie1.find(&"car".to_string()).is_none(), "Removed word found (\"car\")");
+        assert!(trie1.find(&"cares".to_string()).is_some(), "Removing a prefix removed the longer word (\"cares\")");
+        assert_eq!(13, trie1.get_node_count(), "Removing a prefix pruned nodes still in use");
+        assert_eq!(3, trie1.get_word_count(), "Incorrect word count after removal");
+
+        assert_eq!(1, trie1.remove(&"caress".to_string()), "Removal did not return the word's frequency");
+        assert_eq!(12, trie1.get_node_count(), "Orphaned node not pruned after removing \"caress\"");
+
+        assert_eq!(1, trie1.remove(&"baboon".to_string()), "Removal did not return the word's frequency");
+        assert_eq!(6, trie1.get_node_count(), "Orphaned branch not pruned after removing \"baboon\"");
+        assert!(trie1.root.get_child('b').is_none(), "Orphaned branch still reachable");
+
+        assert_eq!(0, trie1.remove(&"care".to_string()), "Removing a prefix that is not a word returned a frequency");
+        assert_eq!(0, trie1.remove(&"zebra".to_string()), "Removing a missing word returned a frequency");
+        assert_eq!(0, trie1.remove(&"".to_string()), "Removing the empty word returned a frequency");
+        assert_eq!(6, trie1.get_node_count(), "Removing words that are not there changed the node count");
+        assert_eq!(1, trie1.get_word_count(), "Removing words that are not there changed the word count");
+
+        trie2.add(&"cares".to_string());
+        assert_eq!(trie2, trie1, "Trie after removals not equal to one built without the words");
+
+        trie1.remove(&"cares".to_string());
+        assert_eq!(Trie::new(), *trie1, "Trie with every word removed not equal to an empty trie");
+        assert_eq!(1, trie1.get_node_count(), "Root pruned or nodes left after removing every word");
+    }
+
+    #[test]
+    fn test_decrement_and_set_freq() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        let trie2 = &mut pair.1;
+
+        trie1.add(&"car".to_string());
+        trie1.add(&"car".to_string());
+        trie1.add(&"cares".to_string());
+
+        assert_eq!(1, trie1.decrement(&"car".to_string()), "Incorrect frequency after decrement");
+        assert_eq!(2, trie1.get_word_count(), "Decrement above zero changed the word count");
+        assert_eq!(0, trie1.decrement(&"car".to_string()), "Incorrect frequency after last decrement");
+        assert!(trie1.find(&"car".to_string()).is_none(), "Word found after decrementing to zero");
+        assert_eq!(1, trie1.get_word_count(), "Decrement to zero did not change the word count");
+        assert_eq!(0, trie1.decrement(&"car".to_string()), "Decrementing a missing word returned a frequency");
+
+        trie1.set_freq(&"cares".to_string(), 5);
+        assert_eq!(5, trie1.find(&"cares".to_string()).unwrap().get_freq(), "Frequency not set");
+        trie1.set_freq(&"Baboon".to_string(), 3);
+        assert_eq!(3, trie1.find(&"baboon".to_string()).unwrap().get_freq(), "Setting a missing word's frequency did not add it");
+        assert_eq!(2, trie1.get_word_count(), "Incorrect word count after setting a new word's frequency");
+        assert_eq!(12, trie1.get_node_count(), "Incorrect node count after setting a new word's frequency");
+
+        for _ in 0..5 {
+            trie2.add(&"cares".to_string());
+        }
+        for _ in 0..3 {
+            trie2.add(&"baboon".to_string());
+        }
+        assert_eq!(trie2, trie1, "Trie built with set_freq not equal to one built by adding");
+
+        trie1.set_freq(&"baboon".to_string(), 0);
+        assert!(trie1.find(&"baboon".to_string()).is_none(), "Word found after setting its frequency to zero");
+        assert_eq!(6, trie1.get_node_count(), "Orphaned branch not pruned after setting frequency to zero");
+        assert_eq!(1, trie1.get_word_count(), "Incorrect word count after setting frequency to zero");
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+
+        for (word, freq) in [("car", 5), ("cares", 2), ("caress", 7), ("cart", 2), ("cat", 9), ("baboon", 1)] {
+            for _ in 0..freq {
+                trie1.add(&word.to_string());
+            }
+        }
+        let mut trie2 = trie1.clone();
+        trie2.set_max_freq_cache(true);
+        assert!(trie2.has_max_freq_cache(), "Cache not enabled");
+
+        for trie in [&*trie1, &trie2] {
+            assert_eq!(vec![("cat".to_string(), 9), ("caress".to_string(), 7), ("car".to_string(), 5)], trie.complete(&"ca".to_string(), 3), "Incorrect top three completions of \"ca\"");
+            assert_eq!(vec![("caress".to_string(), 7), ("car".to_string(), 5), ("cares".to_string(), 2), ("cart".to_string(), 2)], trie.complete(&"CAR".to_string(), 10), "Ties not broken alphabetically");
+            assert_eq!(vec![("baboon".to_string(), 1)], trie.complete(&"baboon".to_string(), 3), "Prefix that is a whole word not completed to itself");
+            assert_eq!(6, trie.complete(&"".to_string(), 10).len(), "Empty prefix did not complete to every word");
+            assert!(trie.complete(&"dog".to_string(), 3).is_empty(), "Completed a missing prefix");
+            assert!(trie.complete(&"ca".to_string(), 0).is_empty(), "Completed with k of zero");
+        }
+        assert_eq!(9, trie2.root.get_max_freq(), "Incorrect cached maximum at the root");
+        assert_eq!(trie1.clone(), trie2, "Cache changed equality");
+    }
+
+    #[test]
+    fn test_max_freq_cache() {
+        let mut pair = setup();
+        let trie1 = &mut pair.0;
+        trie1.set_max_freq_cache(true);
+
+        trie1.add(&"car".to_string());
+        trie1.add(&"cares".to_string());
+        trie1.add(&"cares".to_string());
+        assert_eq!(2, trie1.root.get_child('c').unwrap().get_max_freq(), "Cache not raised by add");
+
+        trie1.set_freq(&"car".to_string(), 4);
+        assert_eq!(vec![("car".to_string(), 4), ("cares".to_string(), 2)], trie1.complete(&"c".to_string(), 2), "Completions ignore a raised frequency");
+
+        trie1.set_freq(&"car".to_string(), 1);
+        assert_eq!(2, trie1.root.get_child('c').unwrap().get_max_freq(), "Cache not lowered by set_freq");
+        assert_eq!(vec![("cares".to_string(), 2), ("car".to_string(), 1)], trie1.complete(&"c".to_string(), 2), "Completions ignore a lowered frequency");
+
+        trie1.remove(&"cares".to_string());
+        assert_eq!(1, trie1.root.get_max_freq(), "Cache not lowered by remove");
+        assert_eq!(vec![("car".to_string(), 1)], trie1.complete(&"c".to_string(), 2), "Removed word completed");
+
+        trie1.decrement(&"car".to_string());
+        assert_eq!(0, trie1.root.get_max_freq(), "Cache not cleared after every word was removed");
+        assert!(trie1.complete(&"".to_string(), 2).is_empty(), "Completed from an empty trie");
+    }
+
     #[test]
     fn test_large_trie() {
         let mut pair = setup();
@@ -468,5 +1304,52 @@ mod tests {
         assert_eq!(trie2.get_node_count(),trie1.get_node_count(), "Equal Trie objects found unequal during million+ word add (including many duplicates)");
         assert_eq!(29157,trie1.get_word_count(), "Incorrect word count after many adds");
     }
+
+    fn counts(words: &[String]) -> BTreeMap<String, u32> {
+        let mut counts = BTreeMap::new();
+        for word in words {
+            *counts.entry(word.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    proptest! {
+        #[test]
+        fn test_equals_ignores_insertion_order(words in prop::collection::vec("[a-z]{1,6}", 0..40)) {
+            let trie1: Trie = words.iter().map(|word| word.as_str()).collect();
+            let trie2: Trie = words.iter().rev().map(|word| word.as_str()).collect();
+            prop_assert_eq!(&trie1, &trie2, "Same words in a different order found unequal");
+            prop_assert_eq!(hash(&trie1), hash(&trie2), "Equal tries return different hashes");
+            prop_assert_eq!(trie1.digest(), trie2.digest(), "Equal tries return different digests");
+        }
+
+        #[test]
+        fn test_equals_matches_word_counts(words1 in prop::collection::vec("[a-e]{1,4}", 0..20), words2 in prop::collection::vec("[a-e]{1,4}", 0..20)) {
+            let trie1: Trie = words1.iter().map(|word| word.as_str()).collect();
+            let trie2: Trie = words2.iter().map(|word| word.as_str()).collect();
+            let same = counts(&words1) == counts(&words2);
+            prop_assert_eq!(same, trie1 == trie2, "Equality disagrees with the words and frequencies");
+            prop_assert_eq!(same, trie2 == trie1, "Equality is not symmetric");
+            if same {
+                prop_assert_eq!(hash(&trie1), hash(&trie2), "Equal tries return different hashes");
+            }
+        }
+
+        #[test]
+        fn test_equals_detects_one_change(words in prop::collection::vec("[a-z]{1,6}", 1..40), extra in "[a-z]{1,6}", index in any::<prop::sample::Index>()) {
+            let trie1: Trie = words.iter().map(|word| word.as_str()).collect();
+
+            let mut trie2 = trie1.clone();
+            trie2.add(&extra);
+            prop_assert_ne!(&trie1, &trie2, "Trie with an extra word found equal");
+            prop_assert_ne!(&trie2, &trie1, "Trie with an extra word found equal");
+
+            let word = index.get(&words);
+            let mut trie3 = trie1.clone();
+            trie3.set_freq(word, trie1.find(word).unwrap().get_freq() + 1);
+            prop_assert_ne!(&trie1, &trie3, "Trie with a changed frequency found equal");
+            prop_assert_ne!(&trie3, &trie1, "Trie with a changed frequency found equal");
+        }
+    }
 }
 